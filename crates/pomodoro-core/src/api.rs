@@ -0,0 +1,193 @@
+//! The parts of the HTTP API that don't need a socket, reading requests and writing JSON
+
+use std::{fmt, io::BufRead, time::Duration};
+
+use crate::{
+    config::Mode,
+    utils::{format_time, is_token},
+};
+
+/// Bodies bigger than this are cut off, as the API only ever needs a few numbers
+const MAX_BODY: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+    /// Without the query
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    /// The token from an `Authorization: Bearer` header
+    pub bearer: Option<String>,
+    pub body: String,
+}
+
+impl Request {
+    /// Reads a request up to the end of its body, or `None` if it isn't HTTP
+    pub fn read(reader: &mut impl BufRead) -> Option<Self> {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let mut parts = line.split_whitespace();
+        let method = parts.next()?.to_string();
+        let target = parts.next()?;
+        if !parts.next()?.starts_with("HTTP/") {
+            return None;
+        }
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (key.to_string(), value.to_string())
+            })
+            .collect();
+
+        let mut bearer = None;
+        let mut length = 0;
+        let mut header = String::new();
+        loop {
+            header.clear();
+            reader.read_line(&mut header).ok()?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let Some((name, value)) = header.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.parse::<usize>().ok()?.min(MAX_BODY);
+            } else if name.eq_ignore_ascii_case("authorization") {
+                bearer = value.strip_prefix("Bearer ").map(str::to_string);
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).ok()?;
+        Some(Self {
+            method,
+            path: path.to_string(),
+            query,
+            bearer,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    }
+
+    /// If it has the token as a bearer token. The event stream can also have it as `?token=`, as
+    /// that's the only way for a browser's `EventSource` to send it, but nothing else can, so it
+    /// isn't left in logs for requests that change anything
+    pub fn is_authorized(&self, token: Option<&str>) -> bool {
+        let Some(token) = token else {
+            return true;
+        };
+        let query = (self.path == "/events")
+            .then(|| self.query.iter().find(|(k, _)| k == "token"))
+            .flatten()
+            .map(|(_, v)| v.as_str());
+        [self.bearer.as_deref(), query]
+            .into_iter()
+            .flatten()
+            .any(|given| is_token(given, token))
+    }
+}
+
+/// What `GET /status` and the event stream send
+#[derive(Debug, Clone, PartialEq)]
+pub struct Status {
+    pub mode: Mode,
+    pub name: String,
+    pub paused: bool,
+    /// How long is left, or how long it's been going for if it counts up
+    pub time: Duration,
+    pub counts_up: bool,
+    /// How many pomodoros have been done
+    pub cycles: usize,
+}
+
+impl Status {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"mode\":\"{}\",\"name\":{},\"paused\":{},\"time\":{},\"counts_up\":{},\"cycles\":{}}}",
+            self.mode.get_name(),
+            json_string(&self.name),
+            self.paused,
+            self.time.as_secs(),
+            self.counts_up,
+            self.cycles
+        )
+    }
+
+    /// If it's moved on to something else, rather than only ticked down
+    pub fn differs_from(&self, other: &Status) -> bool {
+        self.mode != other.mode || self.name != other.name || self.paused != other.paused
+    }
+}
+
+/// Like `Work 12:34 left, paused`, for `pomodoro status`
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = if self.counts_up { "so far" } else { "left" };
+        write!(f, "{} {} {time}", self.name, format_time(self.time))?;
+        if self.paused {
+            write!(f, ", paused")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The new lengths in a `PUT /config` body, like `{"work_minutes": 50, "break_minutes": 10}`,
+/// either of which can be left out
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConfigUpdate {
+    pub work_time: Option<Duration>,
+    pub break_time: Option<Duration>,
+}
+
+impl ConfigUpdate {
+    pub fn parse(body: &str) -> Result<Self, String> {
+        let inner = body
+            .trim()
+            .strip_prefix('{')
+            .and_then(|b| b.strip_suffix('}'))
+            .ok_or("Expected a JSON object")?;
+        let mut update = Self::default();
+        for field in inner.split(',').filter(|f| !f.trim().is_empty()) {
+            let (key, value) = field.split_once(':').ok_or("Expected `key: value`")?;
+            let minutes: f64 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("`{}` is not a number", value.trim()))?;
+            // at least a second, as phases with no length can't be shown
+            if !(1.0 / 60.0..=24.0 * 60.0).contains(&minutes) {
+                return Err(format!("{minutes} minutes is out of range"));
+            }
+            // to the second, like everything else that shows it. Lengths that aren't whole
+            // minutes are kept, as the sliders only round them once they're moved
+            let length = Some(Duration::from_secs((minutes * 60.0).round() as u64));
+            match key.trim().trim_matches('"') {
+                "work_minutes" => update.work_time = length,
+                "break_minutes" => update.break_time = length,
+                other => return Err(format!("Unknown field `{other}`")),
+            }
+        }
+        Ok(update)
+    }
+}
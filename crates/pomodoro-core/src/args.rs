@@ -0,0 +1,195 @@
+//! What the app can be started with, like `pomodoro --work 50m --break 10m --start`
+
+use std::{path::PathBuf, time::Duration};
+
+use crate::{config::Config, instance::Command, utils::parse_duration};
+
+pub const USAGE: &str = "\
+Usage: pomodoro [options] [status | stats | export]
+
+Options:
+  --config <path>     Use this config file instead of the usual one
+  --work <length>     How long work lasts, like 25m or 1h30m
+  --break <length>    How long breaks last
+  --profile <name>    Switch to one of the profiles in the config
+  --no-sound          Don't play any sounds
+  --save              Write the options above to the config, instead of only using them this time
+  --start             Start the timer right away
+  --compact           Open the compact window
+  --toggle            Start or pause the timer
+  --skip              Move on to the next phase
+  -h, --help          Show this
+
+If the timer is already running, --profile, --start, --toggle and --skip are passed on to it.
+
+The config is the one from --config, then POMODORO_CONFIG, then a config.toml next to the app,
+which keeps the history there too, then the one in the usual place.
+
+Commands:
+  status              Show what the running timer is doing
+  stats               Show how many pomodoros were done
+  export              Write the history out as CSV
+";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Args {
+    /// Used instead of the usual config file
+    pub config: Option<PathBuf>,
+    pub overrides: Overrides,
+    /// If the overrides are written to the config file too
+    pub save: bool,
+    pub compact: bool,
+    pub commands: Vec<Command>,
+    /// Something to do instead of opening the window
+    pub subcommand: Option<Subcommand>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Subcommand {
+    Status,
+    Stats,
+    Export,
+    Help,
+}
+
+impl Args {
+    /// Reads the arguments, without the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // `--work=25m` is the same as `--work 25m`
+            let (flag, mut inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || match inline.take() {
+                Some(value) => Ok(value),
+                None => args.next().ok_or(format!("`{flag}` needs a value")),
+            };
+            match flag.as_str() {
+                "--config" => parsed.config = Some(PathBuf::from(value()?)),
+                "--work" => parsed.overrides.work_time = Some(parse_length(&value()?)?),
+                "--break" => parsed.overrides.break_time = Some(parse_length(&value()?)?),
+                "--profile" => parsed.overrides.profile = Some(value()?),
+                "--no-sound" => parsed.overrides.sound = Some(false),
+                "--save" => parsed.save = true,
+                "--start" => parsed.commands.push(Command::Start),
+                "--compact" => parsed.compact = true,
+                "--toggle" => parsed.commands.push(Command::Toggle),
+                "--skip" => parsed.commands.push(Command::Skip),
+                "-h" | "--help" => parsed.subcommand = Some(Subcommand::Help),
+                "status" | "stats" | "export" if parsed.subcommand.is_some() => {
+                    return Err(format!("`{flag}` can't be used with another command"));
+                }
+                "status" => parsed.subcommand = Some(Subcommand::Status),
+                "stats" => parsed.subcommand = Some(Subcommand::Stats),
+                "export" => parsed.subcommand = Some(Subcommand::Export),
+                _ => return Err(format!("Unknown argument `{flag}`")),
+            }
+            if inline.is_some() {
+                return Err(format!("`{flag}` doesn't take a value"));
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// If there's anything that only applies when starting, which can't be passed on
+    pub fn has_launch_options(&self) -> bool {
+        self.config.is_some()
+            || self.save
+            || self.compact
+            || self.overrides.work_time.is_some()
+            || self.overrides.break_time.is_some()
+            || self.overrides.sound.is_some()
+    }
+
+    /// What gets passed on to an instance that's already running
+    pub fn get_forwarded(&self) -> Vec<Command> {
+        let profile = self.overrides.profile.clone().map(Command::Profile);
+        profile.into_iter().chain(self.commands.clone()).collect()
+    }
+}
+
+fn parse_length(s: &str) -> Result<Duration, String> {
+    parse_duration(s)
+        .filter(|length| !length.is_zero())
+        .ok_or(format!("`{s}` is not a length, like 25m or 1h30m"))
+}
+
+/// Settings from the command line, which only last until the app is closed unless `--save` is
+/// given
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Overrides {
+    pub work_time: Option<Duration>,
+    pub break_time: Option<Duration>,
+    pub profile: Option<String>,
+    pub sound: Option<bool>,
+}
+
+impl Overrides {
+    /// Changes the config, giving back what's needed to leave the changes out when it's saved
+    pub fn apply(&self, config: &mut Config) -> Result<Applied, String> {
+        let before = Settings::get(config);
+        if let Some(name) = &self.profile {
+            if !config.set_profile(name) {
+                return Err(format!("There's no profile called `{name}`"));
+            }
+        }
+        // like changing them in the window, which leaves the profile too
+        if let Some(work_time) = self.work_time {
+            config.work_time = work_time;
+            config.profile = None;
+        }
+        if let Some(break_time) = self.break_time {
+            config.break_time = break_time;
+            config.profile = None;
+        }
+        if let Some(sound) = self.sound {
+            config.sound = sound;
+        }
+        Ok(Applied {
+            before,
+            after: Settings::get(config),
+        })
+    }
+}
+
+/// The parts of the config that can be overridden
+#[derive(Debug, Clone, PartialEq)]
+struct Settings {
+    /// Work, break and the profile go together, as switching profile changes all of them
+    times: (Duration, Duration, Option<String>),
+    sound: bool,
+}
+
+impl Settings {
+    fn get(config: &Config) -> Self {
+        Self {
+            times: (config.work_time, config.break_time, config.profile.clone()),
+            sound: config.sound,
+        }
+    }
+}
+
+/// What overriding changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Applied {
+    before: Settings,
+    after: Settings,
+}
+
+impl Applied {
+    /// Puts back what the config had before, unless it's been changed again since
+    pub fn revert(&self, config: &mut Config) {
+        let now = Settings::get(config);
+        if now.times == self.after.times {
+            (config.work_time, config.break_time, config.profile) = self.before.times.clone();
+        }
+        if now.sound == self.after.sound {
+            config.sound = self.before.sound;
+        }
+    }
+}
@@ -0,0 +1,537 @@
+use std::{path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{events::EventKind, phase::PomodoroPhase, schedule::Schedule};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub mode: Mode,
+    pub work_time: Duration,
+    pub break_time: Duration,
+    /// Steps to go through in pomodoro mode instead of work then break, looping back to the start
+    pub sequence: Vec<Step>,
+    pub flowtime: Flowtime,
+    pub countdown_time: Duration,
+    /// How long it takes the ring to fill up in stopwatch mode
+    pub lap_length: Duration,
+    pub compact_position: Option<[f32; 2]>,
+    pub window_size: Option<[f32; 2]>,
+    pub window_position: Option<[f32; 2]>,
+    pub profile: Option<String>,
+    pub profiles: Vec<Profile>,
+    pub theme: Theme,
+    /// If sounds are played when phases and timers end
+    pub sound: bool,
+    /// What is being worked on, passed to the hooks
+    pub task: Option<String>,
+    pub hooks: Hooks,
+    /// Turns on do not disturb while working, only on linux
+    pub do_not_disturb: Option<DndBackend>,
+    pub focus_guard: FocusGuard,
+    pub break_screen: BreakScreen,
+    pub eye_reminders: EyeReminders,
+    pub schedule: Schedule,
+    pub team_sync: TeamSync,
+    pub http_api: HttpApi,
+    pub mqtt: Mqtt,
+    pub folder_sync: FolderSync,
+    /// Timers that run separately from the pomodoro one
+    pub timers: Vec<TimerConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Profile {
+    pub name: String,
+    pub work_time: Duration,
+    pub break_time: Duration,
+}
+
+impl Profile {
+    fn new(name: &str, work_minutes: u64, break_minutes: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            work_time: Duration::from_secs(work_minutes * 60),
+            break_time: Duration::from_secs(break_minutes * 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TimerConfig {
+    pub name: String,
+    pub length: Duration,
+    /// Uses the work color if not set
+    pub color: Option<String>,
+    /// Path to a sound file to play when it's done, instead of the normal one
+    pub sound: Option<PathBuf>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepKind {
+    Work,
+    Break,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Step {
+    pub kind: StepKind,
+    /// Uses `Work` or `Break` if not set
+    pub name: Option<String>,
+    pub length: Duration,
+    /// Uses the work or break color if not set
+    pub color: Option<String>,
+    /// Path to a sound file to play when it's done, instead of the normal one
+    pub sound: Option<PathBuf>,
+}
+
+impl Step {
+    pub fn get_name(&self) -> &str {
+        match (&self.name, self.kind) {
+            (Some(name), _) => name,
+            (None, StepKind::Work) => "Work",
+            (None, StepKind::Break) => "Break",
+        }
+    }
+
+    pub fn to_phase(&self) -> PomodoroPhase {
+        match self.kind {
+            StepKind::Work => PomodoroPhase::new_work(self.length),
+            StepKind::Break => PomodoroPhase::new_break(self.length),
+        }
+    }
+}
+
+impl Config {
+    /// The steps to go through, if there are any and it's in pomodoro mode
+    pub fn get_sequence(&self) -> Option<&[Step]> {
+        match self.mode {
+            Mode::Pomodoro if !self.sequence.is_empty() => Some(&self.sequence),
+            _ => None,
+        }
+    }
+
+    /// Open ended in flowtime mode
+    pub fn new_work_phase(&self) -> PomodoroPhase {
+        match self.mode {
+            Mode::Flowtime => PomodoroPhase::new_open_work(),
+            _ => PomodoroPhase::new_work(self.work_time),
+        }
+    }
+
+    /// How long the break after `worked` of work should be
+    pub fn get_break_time(&self, worked: Duration) -> Duration {
+        match self.mode {
+            Mode::Flowtime => self.flowtime.get_break_time(worked),
+            _ => self.break_time,
+        }
+    }
+
+    /// Catches settings that can't be used, like lengths of zero
+    pub fn validate(&self) -> Result<(), String> {
+        let mut lengths = vec![
+            ("work_time", self.work_time),
+            ("break_time", self.break_time),
+            ("countdown_time", self.countdown_time),
+            ("lap_length", self.lap_length),
+        ];
+        lengths.extend(self.sequence.iter().map(|s| ("sequence step", s.length)));
+        lengths.extend(self.timers.iter().map(|t| ("timer", t.length)));
+        for profile in &self.profiles {
+            lengths.push(("profile work_time", profile.work_time));
+            lengths.push(("profile break_time", profile.break_time));
+        }
+        if let Some((name, _)) = lengths.into_iter().find(|(_, length)| length.is_zero()) {
+            return Err(format!("The {name} in the config can't be zero"));
+        }
+        if self.flowtime.ratio <= 0.0 || self.flowtime.ratio.is_nan() {
+            return Err("The flowtime ratio in the config has to be more than zero".to_string());
+        }
+        if let Some(hours) = self.schedule.hours.iter().find(|h| h.start >= h.end) {
+            return Err(format!(
+                "The working hours from {} to {} in the config end before they start",
+                hours.start, hours.end
+            ));
+        }
+        // MQTT only allows a password along with a username
+        if self.mqtt.password.is_some() && self.mqtt.username.is_none() {
+            return Err("The mqtt password in the config needs a username too".to_string());
+        }
+        Ok(())
+    }
+
+    /// Switches to the profile with the given name, setting the work and break time to it's own
+    pub fn set_profile(&mut self, name: &str) -> bool {
+        let Some(profile) = self.profiles.iter().find(|p| p.name == name) else {
+            return false;
+        };
+        (self.work_time, self.break_time) = (profile.work_time, profile.break_time);
+        self.profile = Some(name.to_string());
+        true
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Pomodoro,
+            work_time: Duration::from_secs(30 * 60),
+            break_time: Duration::from_secs(15 * 60),
+            sequence: vec![],
+            flowtime: Flowtime::default(),
+            countdown_time: Duration::from_secs(7 * 60),
+            lap_length: Duration::from_secs(60),
+            compact_position: None,
+            window_size: None,
+            window_position: None,
+            profile: None,
+            profiles: vec![Profile::new("classic", 25, 5), Profile::new("deep", 50, 10)],
+            theme: Theme::default(),
+            sound: true,
+            task: None,
+            hooks: Hooks::default(),
+            do_not_disturb: None,
+            focus_guard: FocusGuard::default(),
+            break_screen: BreakScreen::default(),
+            eye_reminders: EyeReminders::default(),
+            schedule: Schedule::default(),
+            team_sync: TeamSync::default(),
+            http_api: HttpApi::default(),
+            mqtt: Mqtt::default(),
+            folder_sync: FolderSync::default(),
+            timers: vec![],
+        }
+    }
+}
+
+/// What the main ring is used for
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Pomodoro,
+    /// Work goes on until it's ended, with a break based on how long it went for
+    Flowtime,
+    /// Counts down once from `countdown_time`
+    Countdown,
+    /// Counts up until it's reset
+    Stopwatch,
+}
+
+impl Mode {
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Mode::Pomodoro => "pomodoro",
+            Mode::Flowtime => "flowtime",
+            Mode::Countdown => "countdown",
+            Mode::Stopwatch => "stopwatch",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    Dark,
+    Light,
+    /// Follows whatever the os is set to
+    System,
+    /// Black and white with the brightest phase colors, ignores the other colors
+    HighContrast,
+}
+
+/// Colors are written as hex, like `#3aeb34`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Theme {
+    pub mode: ThemeMode,
+    pub work_color: String,
+    pub break_color: String,
+    /// Uses the normal color for the mode if not set
+    pub background_color: Option<String>,
+    /// Uses the normal color for the mode if not set
+    pub text_color: Option<String>,
+    pub ring_thickness: f32,
+    pub time_font_size: f32,
+    pub phase_font_size: f32,
+    /// Path to a ttf file to use instead of the default font
+    pub font: Option<PathBuf>,
+    /// Only moves the ring once a second
+    pub reduce_motion: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            mode: ThemeMode::Dark,
+            work_color: "#3aeb34".to_string(),
+            break_color: "#dceb34".to_string(),
+            background_color: None,
+            text_color: None,
+            ring_thickness: 7.5,
+            time_font_size: 50.0,
+            phase_font_size: 25.0,
+            font: None,
+            reduce_motion: false,
+        }
+    }
+}
+
+/// Shell commands that get run when the timer changes, with `POMODORO_*` environment variables
+/// describing the phase
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Hooks {
+    pub on_work_start: Option<String>,
+    pub on_work_end: Option<String>,
+    pub on_break_start: Option<String>,
+    pub on_break_end: Option<String>,
+    pub on_pause: Option<String>,
+    pub on_resume: Option<String>,
+}
+
+impl Hooks {
+    pub fn get(&self, kind: EventKind) -> Option<&str> {
+        match kind {
+            EventKind::WorkStart => &self.on_work_start,
+            EventKind::WorkEnd => &self.on_work_end,
+            EventKind::BreakStart => &self.on_break_start,
+            EventKind::BreakEnd => &self.on_break_end,
+            EventKind::Pause => &self.on_pause,
+            EventKind::Resume => &self.on_resume,
+        }
+        .as_deref()
+    }
+}
+
+/// What gets told to hold back notifications
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DndBackend {
+    /// The `Inhibit` call on `org.freedesktop.Notifications`, which KDE has
+    Freedesktop,
+    /// The `show-banners` setting, which is what GNOME's own toggle changes
+    Gnome,
+    Mako,
+    Dunst,
+}
+
+/// Things that get in the way of working, which are warned about or blocked during work phases
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FocusGuard {
+    pub enabled: bool,
+    /// Names of processes to warn about, only on linux
+    pub processes: Vec<String>,
+    /// Hostnames to point at `0.0.0.0`, only if `hosts_file` is set
+    pub hostnames: Vec<String>,
+    /// The hosts file to add the hostnames to, like `/etc/hosts` or a file dnsmasq reads with
+    /// `addn-hosts`, it needs to be writable
+    pub hosts_file: Option<PathBuf>,
+}
+
+/// A screen covering everything during breaks, so they actually get taken
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BreakScreen {
+    pub enabled: bool,
+    /// Things to do during the break, a different one gets shown every so often
+    pub suggestions: Vec<String>,
+    /// How many times each break can be put off for 5 minutes
+    pub max_postpones: u32,
+}
+
+impl Default for BreakScreen {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            suggestions: vec![
+                "Stand up and stretch".to_string(),
+                "Drink a glass of water".to_string(),
+                "Look out of a window, then close your eyes for a bit".to_string(),
+                "Roll your shoulders and neck".to_string(),
+                "Walk around for a minute".to_string(),
+            ],
+            max_postpones: 1,
+        }
+    }
+}
+
+/// Short reminders to rest the eyes during work phases, without ending them
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EyeReminders {
+    pub enabled: bool,
+    /// How much work there is between reminders
+    pub interval: Duration,
+    /// How long each reminder is shown for
+    pub length: Duration,
+    pub message: String,
+}
+
+impl EyeReminders {
+    /// If a reminder should be shown `elapsed` into the work phase, after `given` already were
+    pub fn is_due(&self, elapsed: Duration, given: u32) -> bool {
+        self.enabled && !self.interval.is_zero() && elapsed >= self.interval * (given + 1)
+    }
+}
+
+impl Default for EyeReminders {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: Duration::from_secs(20 * 60),
+            length: Duration::from_secs(20),
+            message: "Look at something 20 feet away".to_string(),
+        }
+    }
+}
+
+/// How long breaks are in flowtime mode
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Flowtime {
+    /// The break is the time worked divided by this, when there are no tiers
+    pub ratio: f32,
+    /// Work up to each tier's `work` gets it's `break_time`, past the last tier gets the last one
+    pub tiers: Vec<FlowtimeTier>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FlowtimeTier {
+    pub work: Duration,
+    pub break_time: Duration,
+}
+
+impl Flowtime {
+    pub fn get_break_time(&self, worked: Duration) -> Duration {
+        if let Some(tier) = self
+            .tiers
+            .iter()
+            .find(|t| worked <= t.work)
+            .or(self.tiers.last())
+        {
+            return tier.break_time;
+        }
+        // a break with no length can't be shown
+        if self.ratio > 0.0 {
+            worked.div_f32(self.ratio).max(Duration::from_secs(1))
+        } else {
+            Duration::from_secs(1)
+        }
+    }
+}
+
+impl Default for Flowtime {
+    fn default() -> Self {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        Self {
+            ratio: 5.0,
+            tiers: vec![
+                FlowtimeTier {
+                    work: minutes(25),
+                    break_time: minutes(5),
+                },
+                FlowtimeTier {
+                    work: minutes(50),
+                    break_time: minutes(8),
+                },
+                FlowtimeTier {
+                    work: minutes(90),
+                    break_time: minutes(10),
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TeamSyncMode {
+    /// Runs the session for others to join
+    Host,
+    /// Follows the timer of the host at `address`
+    Join,
+}
+
+/// Taking breaks together, by following the timer of one person on the network
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TeamSync {
+    /// Off if not set
+    pub mode: Option<TeamSyncMode>,
+    /// What the host listens on, or the host to join
+    pub address: String,
+    /// What others see this as, the user's name if it's empty
+    pub name: String,
+}
+
+impl Default for TeamSync {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            address: "127.0.0.1:7878".to_string(),
+            name: String::new(),
+        }
+    }
+}
+
+/// A small HTTP server for scripts and dashboards to control the timer with
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HttpApi {
+    pub enabled: bool,
+    pub address: String,
+    /// Needed as a bearer token on every request if it's set
+    pub token: Option<String>,
+}
+
+impl Default for HttpApi {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:7879".to_string(),
+            token: None,
+        }
+    }
+}
+
+/// Publishes what the timer is doing to an MQTT broker, and takes commands from it
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Mqtt {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Goes in front of the topics, like `pomodoro/phase`, with commands read from
+    /// `pomodoro/command`
+    pub topic: String,
+}
+
+impl Default for Mqtt {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "pomodoro".to_string(),
+            username: None,
+            password: None,
+            topic: "pomodoro".to_string(),
+        }
+    }
+}
+
+/// Keeps the history and config in a folder shared between devices
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FolderSync {
+    /// Like a Syncthing or Nextcloud folder, syncing is off without one
+    pub folder: Option<PathBuf>,
+    /// Encrypts what's written to the folder, every device needs the same one
+    pub passphrase: Option<String>,
+}
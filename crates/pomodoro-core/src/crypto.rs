@@ -0,0 +1,82 @@
+//! Encryption for the sync folder, so it can be kept on a shared drive. Argon2 turns the
+//! passphrase into the key, and ChaCha20-Poly1305 encrypts each line while catching anything
+//! changed, or a wrong passphrase
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Nonce,
+};
+
+pub const NONCE_LENGTH: usize = 12;
+
+/// How much work it is to turn the passphrase into the key, kept in the key file so it can be
+/// made harder later on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cost {
+    /// In KiB
+    pub memory: u32,
+    pub passes: u32,
+}
+
+impl Default for Cost {
+    /// What argon2 recommends
+    fn default() -> Self {
+        Self {
+            memory: Params::DEFAULT_M_COST,
+            passes: Params::DEFAULT_T_COST,
+        }
+    }
+}
+
+pub struct Key {
+    cipher: ChaCha20Poly1305,
+}
+
+impl Key {
+    /// Slow on purpose, so guessing the passphrase is expensive
+    pub fn derive(passphrase: &str, salt: &[u8], cost: Cost) -> Result<Self, String> {
+        let params =
+            Params::new(cost.memory, cost.passes, 1, Some(32)).map_err(|e| e.to_string())?;
+        let mut key = [0; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            cipher: ChaCha20Poly1305::new(&key.into()),
+        })
+    }
+
+    /// The nonce, then the ciphertext. The nonce has to be different every time, so it should be
+    /// random. `associated` isn't encrypted or stored, but it has to be the same to decrypt
+    pub fn encrypt(
+        &self,
+        nonce: [u8; NONCE_LENGTH],
+        plaintext: &[u8],
+        associated: &[u8],
+    ) -> Vec<u8> {
+        let payload = Payload {
+            msg: plaintext,
+            aad: associated,
+        };
+        // this only fails for more than 256 GiB
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .expect("Should be short enough");
+        [&nonce[..], &ciphertext].concat()
+    }
+
+    /// `None` if it or `associated` was changed, or it was encrypted with another passphrase
+    pub fn decrypt(&self, data: &[u8], associated: &[u8]) -> Option<Vec<u8>> {
+        if data.len() < NONCE_LENGTH {
+            return None;
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LENGTH);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: associated,
+        };
+        self.cipher.decrypt(Nonce::from_slice(nonce), payload).ok()
+    }
+}
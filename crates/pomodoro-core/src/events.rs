@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    WorkStart,
+    WorkEnd,
+    BreakStart,
+    BreakEnd,
+    Pause,
+    Resume,
+}
+
+impl EventKind {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::WorkStart => "work_start",
+            Self::WorkEnd => "work_end",
+            Self::BreakStart => "break_start",
+            Self::BreakEnd => "break_end",
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+        }
+    }
+}
+
+/// Something that happened to the timer, along with the phase it happened to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub kind: EventKind,
+    /// The name of the phase, `Work` or `Break`
+    pub phase: &'static str,
+    /// How long the phase is
+    pub duration: Duration,
+    /// Which work/break cycle the phase is part of, starting at 1
+    pub cycle: usize,
+}
@@ -0,0 +1,117 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Everything that has been recorded, oldest first
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct History {
+    #[serde(default, rename = "record")]
+    pub records: Vec<Record>,
+}
+
+impl History {
+    /// How many work phases ran to the end from `since` on, in seconds since the unix epoch
+    pub fn completed_work_since(&self, since: u64) -> usize {
+        self.summarize(since).completed
+    }
+
+    /// Adds up the pomodoros from `since` on, in seconds since the unix epoch
+    pub fn summarize(&self, since: u64) -> Summary {
+        let mut summary = Summary::default();
+        for record in self.records.iter().filter(|record| record.at >= since) {
+            let Entry::Pomodoro {
+                phase,
+                length,
+                skipped,
+            } = &record.entry
+            else {
+                continue;
+            };
+            if phase != "Work" {
+                continue;
+            }
+            if *skipped {
+                summary.skipped += 1;
+            } else {
+                summary.completed += 1;
+            }
+            summary.worked += Duration::from_secs(*length);
+        }
+        summary
+    }
+
+    /// One row per record, with the time in seconds since the unix epoch and lengths in seconds
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("at,kind,phase,length,skipped,acknowledged,laps\n");
+        for record in &self.records {
+            let row = match &record.entry {
+                Entry::Pomodoro {
+                    phase,
+                    length,
+                    skipped,
+                } => format!("pomodoro,{},{length},{skipped},,", csv_field(phase)),
+                Entry::Countdown { length } => format!("countdown,,{length},,,"),
+                Entry::Stopwatch { elapsed, laps } => {
+                    let laps: Vec<String> = laps.iter().map(u64::to_string).collect();
+                    format!("stopwatch,,{elapsed},,,{}", laps.join(";"))
+                }
+                Entry::Reminder { acknowledged } => format!("reminder,,,,{acknowledged},"),
+            };
+            csv.push_str(&format!("{},{row}\n", record.at));
+        }
+        csv
+    }
+}
+
+/// Quoted if it has anything in it that would break the row up
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// The work done over some time
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Summary {
+    pub completed: usize,
+    pub skipped: usize,
+    /// Including the time spent on the skipped ones
+    pub worked: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Record {
+    /// Seconds since the unix epoch
+    pub at: u64,
+    #[serde(flatten)]
+    pub entry: Entry,
+}
+
+impl Record {
+    pub fn now(entry: Entry) -> Self {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self { at, entry }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Entry {
+    /// A work or break phase that ended, lengths are in seconds
+    Pomodoro {
+        phase: String,
+        length: u64,
+        skipped: bool,
+    },
+    /// A countdown that ran out
+    Countdown { length: u64 },
+    /// A stopwatch that was reset, with how long each lap took
+    Stopwatch { elapsed: u64, laps: Vec<u64> },
+    /// An eye strain reminder, and if it was clicked away before it ran out
+    Reminder { acknowledged: bool },
+}
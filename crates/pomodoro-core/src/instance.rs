@@ -0,0 +1,91 @@
+//! What a second launch passes on to the one already running, the token from the port file
+//! and then one command per line
+
+use std::{fmt, path::Path};
+
+/// The answer from the running instance, so something else that happens to be on the port
+/// isn't taken for it
+pub const ACKNOWLEDGE: &str = "ok";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Brings the window to the front, which a second launch always does
+    Show,
+    Start,
+    Toggle,
+    Skip,
+    Profile(String),
+    /// Asks what the timer is doing, which gets answered before the acknowledgement
+    Status,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (kind, rest) = line.split_once('\t').unwrap_or((line, ""));
+        match kind {
+            "show" => Some(Self::Show),
+            "start" => Some(Self::Start),
+            "toggle" => Some(Self::Toggle),
+            "skip" => Some(Self::Skip),
+            "profile" if !rest.is_empty() => Some(Self::Profile(rest.to_string())),
+            "status" => Some(Self::Status),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Show => write!(f, "show"),
+            Self::Start => write!(f, "start"),
+            Self::Toggle => write!(f, "toggle"),
+            Self::Skip => write!(f, "skip"),
+            Self::Profile(name) => write!(f, "profile\t{name}"),
+            Self::Status => write!(f, "status"),
+        }
+    }
+}
+
+/// What the lock and port files are called for a config file, so copies using different configs
+/// each run on their own
+pub fn get_name(config_file: &Path) -> String {
+    // FNV-1a, which stays the same between builds, unlike the standard library's hasher
+    let hash = config_file
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+        });
+    format!("instance-{hash:016x}")
+}
+
+/// What the running instance writes so later launches can find it. Only the user can read it,
+/// and the token has to be sent before any commands, so other users can't send any
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortFile {
+    pub port: u16,
+    pub token: String,
+}
+
+impl PortFile {
+    pub fn parse(contents: &str) -> Option<Self> {
+        let (port, token) = contents.trim().split_once('\t')?;
+        if token.is_empty() {
+            return None;
+        }
+        Some(Self {
+            port: port.parse().ok()?,
+            token: token.to_string(),
+        })
+    }
+}
+
+/// Like `43210\t<token>`
+impl fmt::Display for PortFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\t{}", self.port, self.token)
+    }
+}
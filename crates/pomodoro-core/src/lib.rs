@@ -0,0 +1,31 @@
+//! The timer behind the pomodoro app, without anything to do with drawing it
+
+pub mod api;
+pub mod args;
+pub mod clock;
+pub mod config;
+pub mod crypto;
+pub mod events;
+pub mod history;
+pub mod instance;
+pub mod mqtt;
+pub mod named;
+pub mod phase;
+pub mod schedule;
+pub mod stats;
+pub mod stopwatch;
+pub mod sync;
+pub mod team;
+pub mod timer;
+pub mod utils;
+
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use config::Config;
+pub use events::{Event, EventKind};
+pub use history::{Entry, History, Record};
+pub use named::NamedTimer;
+pub use phase::PomodoroPhase;
+pub use stats::Stats;
+pub use stopwatch::Stopwatch;
+pub use timer::Timer;
+pub use utils::{format_time, Percent};
@@ -0,0 +1,169 @@
+//! Just enough of MQTT 3.1.1 to publish what the timer is doing and take commands, all at QoS 0
+
+use std::io::{self, Read};
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const PINGREQ: u8 = 0xC0;
+const PINGRESP: u8 = 0xD0;
+const DISCONNECT: u8 = 0xE0;
+/// The longest a packet can be after its first byte, as the length can only take up four bytes
+const MAX_LENGTH: usize = 128 * 128 * 128 * 128 - 1;
+
+/// The packets a broker sends that get looked at
+#[derive(Debug, Clone, PartialEq)]
+pub enum Packet {
+    /// `code` is 0 if the connection was accepted
+    ConnAck {
+        code: u8,
+    },
+    Publish {
+        topic: String,
+        payload: Vec<u8>,
+    },
+    SubAck,
+    PingResp,
+    /// Anything else, by its first byte
+    Other(u8),
+}
+
+fn push_length(out: &mut Vec<u8>, mut length: usize) {
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+}
+
+fn too_long() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "Too long to send")
+}
+
+/// Strings have their length in front of them in two bytes
+fn push_str(out: &mut Vec<u8>, s: &str) -> io::Result<()> {
+    let length = u16::try_from(s.len()).map_err(|_| too_long())?;
+    out.extend_from_slice(&length.to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+/// Puts the fixed header in front of the rest of the packet
+fn packet(first: u8, body: Vec<u8>) -> io::Result<Vec<u8>> {
+    if body.len() > MAX_LENGTH {
+        return Err(too_long());
+    }
+    let mut out = vec![first];
+    push_length(&mut out, body.len());
+    out.extend(body);
+    Ok(out)
+}
+
+/// A password is left out if there's no username, as brokers close the connection otherwise
+pub fn connect(
+    client_id: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+    keep_alive: u16,
+) -> io::Result<Vec<u8>> {
+    let password = username.and(password);
+    let mut body = vec![];
+    push_str(&mut body, "MQTT")?;
+    // protocol level 4 is 3.1.1
+    body.push(4);
+    // always a clean session
+    let mut flags = 0x02;
+    if username.is_some() {
+        flags |= 0x80;
+    }
+    if password.is_some() {
+        flags |= 0x40;
+    }
+    body.push(flags);
+    body.extend_from_slice(&keep_alive.to_be_bytes());
+    push_str(&mut body, client_id)?;
+    for field in [username, password].into_iter().flatten() {
+        push_str(&mut body, field)?;
+    }
+    packet(CONNECT, body)
+}
+
+pub fn publish(topic: &str, payload: &[u8], retain: bool) -> io::Result<Vec<u8>> {
+    let mut body = vec![];
+    push_str(&mut body, topic)?;
+    body.extend_from_slice(payload);
+    packet(PUBLISH | u8::from(retain), body)
+}
+
+pub fn subscribe(packet_id: u16, topic: &str) -> io::Result<Vec<u8>> {
+    let mut body = packet_id.to_be_bytes().to_vec();
+    push_str(&mut body, topic)?;
+    // QoS 0
+    body.push(0);
+    packet(SUBSCRIBE, body)
+}
+
+pub fn ping() -> Vec<u8> {
+    vec![PINGREQ, 0]
+}
+
+pub fn disconnect() -> Vec<u8> {
+    vec![DISCONNECT, 0]
+}
+
+/// Reads the next packet, blocking until it's all there
+pub fn read_packet(reader: &mut impl Read) -> io::Result<Packet> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    let first = byte[0];
+    let mut length = 0;
+    let mut multiplier = 1;
+    loop {
+        reader.read_exact(&mut byte)?;
+        length += usize::from(byte[0] & 0x7F) * multiplier;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        multiplier *= 128;
+        if multiplier > 128 * 128 * 128 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Length too long",
+            ));
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Packet too short");
+    let packet = match first & 0xF0 {
+        CONNACK => Packet::ConnAck {
+            code: *body.get(1).ok_or_else(invalid)?,
+        },
+        PUBLISH => {
+            let topic_length = usize::from(u16::from_be_bytes([
+                *body.first().ok_or_else(invalid)?,
+                *body.get(1).ok_or_else(invalid)?,
+            ]));
+            let topic = body.get(2..2 + topic_length).ok_or_else(invalid)?;
+            let topic = String::from_utf8_lossy(topic).into_owned();
+            // QoS 1 and 2 have a packet id before the payload
+            let qos = (first >> 1) & 0x03;
+            let start = 2 + topic_length + if qos > 0 { 2 } else { 0 };
+            let payload = body.get(start..).ok_or_else(invalid)?.to_vec();
+            Packet::Publish { topic, payload }
+        }
+        SUBACK => Packet::SubAck,
+        PINGRESP => Packet::PingResp,
+        _ => Packet::Other(first),
+    };
+    Ok(packet)
+}
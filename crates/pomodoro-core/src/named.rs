@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use crate::{
+    clock::{Clock, SystemClock},
+    phase::PomodoroPhase,
+    utils::Percent,
+};
+
+/// A single countdown that runs separately from the pomodoro timer, like for a meeting or tea
+pub struct NamedTimer<C: Clock = SystemClock> {
+    clock: C,
+    name: String,
+    phase: PomodoroPhase,
+}
+
+impl NamedTimer {
+    pub fn new(name: &str, length: Duration) -> Self {
+        Self::with_clock(SystemClock, name, length)
+    }
+}
+
+impl<C: Clock> NamedTimer<C> {
+    pub fn with_clock(clock: C, name: &str, length: Duration) -> Self {
+        Self {
+            clock,
+            name: name.to_string(),
+            phase: PomodoroPhase::new_work(length),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_duration(&self) -> Duration {
+        self.phase.get_duration().unwrap_or_default()
+    }
+
+    pub fn toggle(&mut self) {
+        self.phase.toggle(self.clock.now());
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.phase.is_paused()
+    }
+
+    pub fn get_time_left(&self) -> Duration {
+        self.phase.get_time_left(self.clock.now())
+    }
+
+    pub fn to_percent(&self) -> Option<Percent> {
+        self.phase.to_percent(self.clock.now())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.phase.is_done(self.clock.now())
+    }
+
+    /// Puts it back to the start, paused
+    pub fn reset(&mut self) {
+        self.phase = PomodoroPhase::new_work(self.get_duration());
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::{Duration, Instant};
+
+use crate::utils::Percent;
+
+/// `length` is `None` for phases that go on until they are ended
+pub enum PomodoroPhase {
+    Work {
+        start: Option<Instant>,
+        length: Option<Duration>,
+        paused: Option<Duration>,
+    },
+    Break {
+        start: Option<Instant>,
+        length: Option<Duration>,
+        paused: Option<Duration>,
+    },
+}
+
+impl PomodoroPhase {
+    pub fn new_work(length: Duration) -> Self {
+        Self::Work {
+            start: None,
+            length: Some(length),
+            paused: Some(Duration::new(0, 0)),
+        }
+    }
+
+    /// A work phase with no length, that counts up until it's ended
+    pub fn new_open_work() -> Self {
+        Self::Work {
+            start: None,
+            length: None,
+            paused: Some(Duration::new(0, 0)),
+        }
+    }
+
+    pub fn new_break(length: Duration) -> Self {
+        Self::Break {
+            start: None,
+            length: Some(length),
+            paused: Some(Duration::new(0, 0)),
+        }
+    }
+
+    pub fn get_duration(&self) -> Option<Duration> {
+        match self {
+            Self::Work { length, .. } | Self::Break { length, .. } => *length,
+        }
+    }
+
+    pub fn is_open_ended(&self) -> bool {
+        self.get_duration().is_none()
+    }
+
+    pub fn get_start(&self) -> Option<Instant> {
+        match self {
+            Self::Work { start, .. } | Self::Break { start, .. } => *start,
+        }
+    }
+
+    pub fn start(&mut self, now: Instant) {
+        let sub = match self {
+            Self::Work { paused, .. } | Self::Break { paused, .. } => paused.take(),
+        }
+        .unwrap_or(Duration::new(0, 0));
+        match self {
+            Self::Work { start, .. } | Self::Break { start, .. } => *start = Some(now - sub),
+        }
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        match self {
+            Self::Work { start, paused, .. } | Self::Break { start, paused, .. } => {
+                if let Some(start_time) = start.take() {
+                    *paused = Some(now.saturating_duration_since(start_time));
+                }
+            }
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        match self {
+            Self::Break { paused, .. } | Self::Work { paused, .. } => paused.is_some(),
+        }
+    }
+
+    pub fn toggle(&mut self, now: Instant) {
+        if self.is_paused() {
+            self.start(now);
+        } else {
+            self.pause(now);
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Work { .. } => "Work",
+            Self::Break { .. } => "Break",
+        }
+    }
+
+    pub fn time_elapsed(&self, now: Instant) -> Option<Duration> {
+        match self {
+            Self::Work { paused, start, .. } | Self::Break { paused, start, .. } => {
+                if let Some(paused_time) = *paused {
+                    Some(paused_time)
+                } else {
+                    start.map(|start_time| now.saturating_duration_since(start_time))
+                }
+            }
+        }
+    }
+
+    /// If the phase is running and has gone on for longer than it's length
+    pub fn is_done(&self, now: Instant) -> bool {
+        match (self.get_start(), self.get_duration()) {
+            (Some(start), Some(length)) => now.saturating_duration_since(start) > length,
+            _ => false,
+        }
+    }
+
+    /// Always zero for open ended phases
+    pub fn get_time_left(&self, now: Instant) -> Duration {
+        self.get_duration().map_or(Duration::ZERO, |length| {
+            length.saturating_sub(self.time_elapsed(now).unwrap_or(Duration::new(0, 0)))
+        })
+    }
+
+    /// Like `to_percent`, but only changes once every second
+    pub fn to_stepped_percent(&self, now: Instant) -> Option<Percent> {
+        self.time_elapsed(now)
+            .map(|elapsed| self.percent_of(Duration::from_secs(elapsed.as_secs())))
+    }
+
+    pub fn to_percent(&self, now: Instant) -> Option<Percent> {
+        self.time_elapsed(now)
+            .map(|elapsed| self.percent_of(elapsed))
+    }
+
+    /// Open ended phases stay full, as there is nothing to count down to, and ones with no length
+    /// are already empty
+    fn percent_of(&self, time_elapsed: Duration) -> Percent {
+        let Some(length) = self.get_duration() else {
+            return Percent::new(100.0).expect("Should be valid");
+        };
+        if length.is_zero() {
+            return Percent::new(0.0).expect("Should be valid");
+        }
+        Percent::new(
+            100.0
+                - ((time_elapsed.as_nanos() as f64 / length.as_nanos() as f64) * 100.0)
+                    .clamp(0.0, 100.0) as f32,
+        )
+        .expect("Should be valid")
+    }
+}
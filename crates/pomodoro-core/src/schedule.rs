@@ -0,0 +1,235 @@
+//! Working hours through the week, worked out from a local time passed in rather than the real
+//! clock
+
+use std::{fmt, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+const MINUTES_PER_WEEK: u32 = 7 * MINUTES_PER_DAY;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl Weekday {
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ];
+
+    /// Counts from monday, which is 0
+    pub fn from_index(i: usize) -> Self {
+        Self::ALL[i % 7]
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Weekday::Mon => "Mon",
+            Weekday::Tue => "Tue",
+            Weekday::Wed => "Wed",
+            Weekday::Thu => "Thu",
+            Weekday::Fri => "Fri",
+            Weekday::Sat => "Sat",
+            Weekday::Sun => "Sun",
+        }
+    }
+}
+
+/// A time of day to the minute, written as `HH:MM` in the config
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay {
+    /// Minutes since midnight
+    minutes: u16,
+}
+
+impl TimeOfDay {
+    pub fn new(hour: u16, minute: u16) -> Option<Self> {
+        (hour < 24 && minute < 60).then_some(Self {
+            minutes: hour * 60 + minute,
+        })
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.minutes / 60, self.minutes % 60)
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.split_once(':')
+            .and_then(|(h, m)| Self::new(h.trim().parse().ok()?, m.trim().parse().ok()?))
+            .ok_or_else(|| format!("`{s}` is not a time like 09:30"))
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        time.to_string()
+    }
+}
+
+/// A moment in the week, in local time
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LocalTime {
+    pub day: Weekday,
+    pub time: TimeOfDay,
+    /// Into the minute, so what's left of the working hours is known to the second
+    pub second: u8,
+}
+
+impl LocalTime {
+    fn from_week_minute(minute: u32) -> Self {
+        Self {
+            day: Weekday::from_index((minute / MINUTES_PER_DAY) as usize),
+            time: TimeOfDay {
+                minutes: (minute % MINUTES_PER_DAY) as u16,
+            },
+            second: 0,
+        }
+    }
+
+    fn week_minute(self) -> u32 {
+        self.day as u32 * MINUTES_PER_DAY + u32::from(self.time.minutes)
+    }
+}
+
+impl fmt::Display for LocalTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.day.get_name(), self.time)
+    }
+}
+
+/// How many minutes it is from `from` to `to`, going forward and wrapping round the week
+fn minutes_until(from: u32, to: u32) -> u32 {
+    (to + MINUTES_PER_WEEK - from) % MINUTES_PER_WEEK
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WorkingHours {
+    pub days: Vec<Weekday>,
+    pub start: TimeOfDay,
+    pub end: TimeOfDay,
+    /// Starts a work phase at `start`
+    pub auto_start: bool,
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        Self {
+            days: Weekday::ALL[..5].to_vec(),
+            start: TimeOfDay::new(9, 0).expect("Should be valid"),
+            end: TimeOfDay::new(17, 0).expect("Should be valid"),
+            auto_start: false,
+        }
+    }
+}
+
+impl WorkingHours {
+    /// When it starts on each of its days, as minutes into the week
+    fn starts(&self) -> impl Iterator<Item = u32> + '_ {
+        self.days
+            .iter()
+            .map(|day| *day as u32 * MINUTES_PER_DAY + u32::from(self.start.minutes))
+    }
+
+    fn contains(&self, now: LocalTime) -> bool {
+        self.days.contains(&now.day) && self.start <= now.time && now.time < self.end
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Schedule {
+    pub enabled: bool,
+    /// Cuts phases short so they end with the working hours, instead of only flagging them
+    pub shorten_phases: bool,
+    pub hours: Vec<WorkingHours>,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        let time = |hour| TimeOfDay::new(hour, 0).expect("Should be valid");
+        // a working day with lunch left out
+        Self {
+            enabled: false,
+            shorten_phases: false,
+            hours: vec![
+                WorkingHours {
+                    start: time(9),
+                    end: time(12),
+                    auto_start: true,
+                    ..Default::default()
+                },
+                WorkingHours {
+                    start: time(13),
+                    end: time(18),
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+}
+
+impl Schedule {
+    /// When the working hours `now` is in end, or `None` outside of them
+    pub fn get_end(&self, now: LocalTime) -> Option<TimeOfDay> {
+        self.hours
+            .iter()
+            .filter(|hours| hours.contains(now))
+            .map(|hours| hours.end)
+            .max()
+    }
+
+    /// How long is left of the working hours `now` is in
+    pub fn time_left(&self, now: LocalTime) -> Option<Duration> {
+        let end = u64::from(self.get_end(now)?.minutes) * 60;
+        let now = u64::from(now.time.minutes) * 60 + u64::from(now.second);
+        Some(Duration::from_secs(end.saturating_sub(now)))
+    }
+
+    pub fn is_working_hours(&self, now: LocalTime) -> bool {
+        self.get_end(now).is_some()
+    }
+
+    /// When the next working hours start after `now`, within the next week
+    pub fn next_session(&self, now: LocalTime) -> Option<LocalTime> {
+        let now = now.week_minute();
+        self.hours
+            .iter()
+            .flat_map(WorkingHours::starts)
+            .filter(|start| *start != now)
+            .min_by_key(|start| minutes_until(now, *start))
+            .map(LocalTime::from_week_minute)
+    }
+
+    /// If working hours that start the timer began after `from`, up to and including `to`
+    pub fn auto_starts_between(&self, from: LocalTime, to: LocalTime) -> bool {
+        let span = minutes_until(from.week_minute(), to.week_minute());
+        self.hours
+            .iter()
+            .filter(|hours| hours.auto_start)
+            .flat_map(WorkingHours::starts)
+            .map(|start| minutes_until(from.week_minute(), start))
+            .any(|until| until > 0 && until <= span)
+    }
+}
@@ -13,4 +13,3 @@ impl Stats {
         self.0 / 2
     }
 }
-
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use crate::{
+    clock::{Clock, SystemClock},
+    phase::PomodoroPhase,
+    utils::Percent,
+};
+
+/// Counts up with no end, the ring fills up once each lap length
+pub struct Stopwatch<C: Clock = SystemClock> {
+    clock: C,
+    phase: PomodoroPhase,
+    /// How long each lap took
+    laps: Vec<Duration>,
+}
+
+impl Stopwatch {
+    pub fn new(lap_length: Duration) -> Self {
+        Self::with_clock(SystemClock, lap_length)
+    }
+}
+
+impl<C: Clock> Stopwatch<C> {
+    pub fn with_clock(clock: C, lap_length: Duration) -> Self {
+        Self {
+            clock,
+            phase: PomodoroPhase::new_work(lap_length),
+            laps: vec![],
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.phase.toggle(self.clock.now());
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.phase.is_paused()
+    }
+
+    pub fn time_elapsed(&self) -> Duration {
+        self.phase
+            .time_elapsed(self.clock.now())
+            .unwrap_or_default()
+    }
+
+    pub fn get_laps(&self) -> &[Duration] {
+        &self.laps
+    }
+
+    /// Ends the current lap, and starts a new one
+    pub fn lap(&mut self) {
+        let previous: Duration = self.laps.iter().sum();
+        self.laps.push(self.time_elapsed().saturating_sub(previous));
+    }
+
+    /// How far into the lap length it is, going from empty to full
+    pub fn to_percent(&self) -> Percent {
+        let lap_length = self
+            .phase
+            .get_duration()
+            .unwrap_or_default()
+            .as_nanos()
+            .max(1);
+        let into_lap = self.time_elapsed().as_nanos() % lap_length;
+        Percent::new((into_lap as f64 / lap_length as f64 * 100.0) as f32).expect("Should be valid")
+    }
+
+    /// Stops it and clears the laps, with a new lap length
+    pub fn reset(&mut self, lap_length: Duration) {
+        self.phase = PomodoroPhase::new_work(lap_length);
+        self.laps.clear();
+    }
+}
@@ -0,0 +1,332 @@
+//! Sharing the history and config between devices through a folder, like one synced by Syncthing
+//! or Nextcloud. Each device only ever adds lines to the end of its own log, so nothing another
+//! device wrote gets written over, and all the logs are merged by when each line was written
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use crate::{
+    crypto::{Cost, Key, NONCE_LENGTH},
+    history::History,
+    Config, Record,
+};
+
+/// Settings that only make sense on the device they were set on, like paths and addresses, or
+/// that run commands, by their path through the tables
+const NOT_SYNCED: [&str; 12] = [
+    "folder_sync",
+    "window_size",
+    "window_position",
+    "compact_position",
+    "hooks",
+    "do_not_disturb",
+    "http_api",
+    "mqtt.client_id",
+    "team_sync.mode",
+    "team_sync.address",
+    "focus_guard.hosts_file",
+    "theme.font",
+];
+
+/// What's encrypted in the key file, to tell if the passphrase is right
+const CHECK: &[u8] = b"pomodoro";
+/// What the check in the key file is encrypted along with, so it can't be used as a log line
+const CHECK_ASSOCIATED: &[u8] = b"key";
+
+/// Like `argon2id\t19456\t2\t<salt>\t<check>`, with how hard the key is to derive, the salt for
+/// the passphrase, and something encrypted with it to check it against. The first device to
+/// use the folder writes it
+pub fn new_key_file(
+    passphrase: &str,
+    salt: &[u8],
+    cost: Cost,
+    nonce: [u8; NONCE_LENGTH],
+) -> Result<(Key, String), String> {
+    let key = Key::derive(passphrase, salt, cost)?;
+    let check = key.encrypt(nonce, CHECK, CHECK_ASSOCIATED);
+    let contents = format!(
+        "argon2id\t{}\t{}\t{}\t{}\n",
+        cost.memory,
+        cost.passes,
+        hex::encode(salt),
+        hex::encode(check)
+    );
+    Ok((key, contents))
+}
+
+pub fn open_key_file(contents: &str, passphrase: &str) -> Result<Key, String> {
+    let broken = || "The key file in the sync folder is broken".to_string();
+    let parts: Vec<&str> = contents.trim().split('\t').collect();
+    let ["argon2id", memory, passes, salt, check] = parts[..] else {
+        return Err(broken());
+    };
+    let cost = Cost {
+        memory: memory.parse().map_err(|_| broken())?,
+        passes: passes.parse().map_err(|_| broken())?,
+    };
+    let salt = hex::decode(salt).map_err(|_| broken())?;
+    let key = Key::derive(passphrase, &salt, cost)?;
+    let check = hex::decode(check)
+        .ok()
+        .and_then(|check| key.decrypt(&check, CHECK_ASSOCIATED));
+    if check.as_deref() != Some(CHECK) {
+        return Err("Wrong passphrase for the sync folder".to_string());
+    }
+    Ok(key)
+}
+
+fn get_not_synced() -> impl Iterator<Item = Vec<String>> {
+    NOT_SYNCED
+        .iter()
+        .map(|path| path.split('.').map(str::to_string).collect())
+}
+
+/// The value a setting has in `table`, by the path through the tables to it, like
+/// `["theme", "mode"]`
+pub fn get_setting<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let parent = parents
+        .iter()
+        .try_fold(table, |table, key| table.get(key)?.as_table())?;
+    parent.get(last)
+}
+
+/// The table at `path`, made if it's not there
+fn table_at<'a>(table: &'a mut Table, path: &[String]) -> &'a mut Table {
+    path.iter().fold(table, |table, key| {
+        let value = table
+            .entry(key.clone())
+            .or_insert_with(|| Value::Table(Table::new()));
+        if !value.is_table() {
+            *value = Value::Table(Table::new());
+        }
+        value.as_table_mut().expect("Should be a table")
+    })
+}
+
+fn remove_setting(table: &mut Table, path: &[String]) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let parent = parents
+        .iter()
+        .try_fold(table, |table, key| table.get_mut(key)?.as_table_mut());
+    if let Some(parent) = parent {
+        parent.remove(last);
+    }
+}
+
+fn set_setting(table: &mut Table, path: &[String], value: Value) {
+    if let Some((last, parents)) = path.split_last() {
+        table_at(table, parents).insert(last.clone(), value);
+    }
+}
+
+/// A setting that was changed, inside a table or not
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Setting {
+    pub path: Vec<String>,
+    pub value: Value,
+}
+
+/// What changed in the config, setting by setting, so changes to different settings in the same
+/// table don't get in each other's way
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ConfigChange {
+    pub set: Vec<Setting>,
+    /// Settings that were unset, which toml can't have as a value
+    pub removed: Vec<Vec<String>>,
+}
+
+impl ConfigChange {
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.removed.is_empty()
+    }
+
+    /// Everything that's different in `to`, going into tables that are in both
+    pub fn between(from: &Table, to: &Table) -> Self {
+        let mut change = Self::default();
+        change.add_between(from, to, &mut vec![]);
+        change
+    }
+
+    fn add_between(&mut self, from: &Table, to: &Table, path: &mut Vec<String>) {
+        for (key, value) in to {
+            path.push(key.clone());
+            match (from.get(key), value) {
+                (Some(Value::Table(from)), Value::Table(to)) => self.add_between(from, to, path),
+                (old, _) if old != Some(value) => self.set.push(Setting {
+                    path: path.clone(),
+                    value: value.clone(),
+                }),
+                _ => {}
+            }
+            path.pop();
+        }
+        for key in from.keys().filter(|k| !to.contains_key(*k)) {
+            let mut removed = path.clone();
+            removed.push(key.clone());
+            self.removed.push(removed);
+        }
+    }
+
+    /// If it changes the setting at `path`, or one inside or around it
+    pub fn touches(&self, path: &[String]) -> bool {
+        let overlaps = |other: &[String]| other.starts_with(path) || path.starts_with(other);
+        self.set.iter().any(|setting| overlaps(&setting.path))
+            || self.removed.iter().any(|removed| overlaps(removed))
+    }
+
+    /// Keeps the settings `keep` is true for, which gets `None` for the removed ones
+    pub fn retain(&mut self, mut keep: impl FnMut(&[String], Option<&Value>) -> bool) {
+        self.set
+            .retain(|setting| keep(&setting.path, Some(&setting.value)));
+        self.removed.retain(|path| keep(path, None));
+    }
+
+    pub fn apply_to_table(&self, table: &mut Table) {
+        for path in &self.removed {
+            remove_setting(table, path);
+        }
+        for Setting { path, value } in &self.set {
+            set_setting(table, path, value.clone());
+        }
+    }
+
+    /// Keeps what `config` has for the settings that aren't synced, even if it came from a log
+    /// that has them
+    pub fn apply(&self, config: &Config) -> Result<Config, String> {
+        let before = Table::try_from(config).map_err(|e| e.to_string())?;
+        let mut table = before.clone();
+        self.apply_to_table(&mut table);
+        for path in get_not_synced() {
+            match get_setting(&before, &path) {
+                Some(value) => set_setting(&mut table, &path, value.clone()),
+                None => remove_setting(&mut table, &path),
+            }
+        }
+        Value::Table(table).try_into().map_err(|e| e.to_string())
+    }
+}
+
+/// The part of the config that gets synced
+pub fn synced_table(config: &Config) -> Result<Table, String> {
+    let mut table = Table::try_from(config).map_err(|e| e.to_string())?;
+    for path in get_not_synced() {
+        remove_setting(&mut table, &path);
+    }
+    Ok(table)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Record(Record),
+    Config(ConfigChange),
+}
+
+/// A line in a device's log
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    /// Milliseconds since the unix epoch
+    pub at: u64,
+    pub change: Change,
+}
+
+/// What each encrypted line is tied to, so it can't be moved to another time or device's log
+fn associated(at: u64, kind: &str, device: &str) -> Vec<u8> {
+    format!("{at}\t{kind}\t{device}").into_bytes()
+}
+
+impl LogEntry {
+    /// Like `1700000000000\trecord\t<hex>`, where what's in hex is toml, encrypted if there's a
+    /// key. `device` is whose log it's for
+    pub fn to_line(
+        &self,
+        device: &str,
+        key: Option<(&Key, [u8; NONCE_LENGTH])>,
+    ) -> Result<String, String> {
+        let (kind, data) = match &self.change {
+            Change::Record(record) => ("record", toml::to_string(record)),
+            Change::Config(change) => ("config", toml::to_string(change)),
+        };
+        let data = data.map_err(|e| e.to_string())?.into_bytes();
+        let data = match key {
+            Some((key, nonce)) => key.encrypt(nonce, &data, &associated(self.at, kind, device)),
+            None => data,
+        };
+        Ok(format!("{}\t{kind}\t{}", self.at, hex::encode(data)))
+    }
+
+    /// `None` if it's not a whole line, or can't be decrypted
+    pub fn parse(line: &str, device: &str, key: Option<&Key>) -> Option<Self> {
+        let mut parts = line.trim_end_matches('\r').split('\t');
+        let at = parts.next()?.parse().ok()?;
+        let kind = parts.next()?;
+        let data = hex::decode(parts.next()?).ok()?;
+        let data = match key {
+            Some(key) => key.decrypt(&data, &associated(at, kind, device))?,
+            None => data,
+        };
+        let data = String::from_utf8(data).ok()?;
+        let change = match kind {
+            "record" => Change::Record(toml::from_str(&data).ok()?),
+            "config" => Change::Config(toml::from_str(&data).ok()?),
+            _ => return None,
+        };
+        Some(Self { at, change })
+    }
+}
+
+/// Reads a whole log, leaving out a last line that's still being written, and how many lines
+/// couldn't be read. Encrypted lines each have their own random nonce, so one that's there twice
+/// was copied and only counts once
+pub fn parse_log(log: &str, device: &str, key: Option<&Key>) -> (Vec<LogEntry>, usize) {
+    let complete = log.rfind('\n').map_or("", |end| &log[..end]);
+    let mut unreadable = 0;
+    let mut seen = HashSet::new();
+    let entries = complete
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| {
+            let copied = key.is_some() && !seen.insert(line);
+            let entry = LogEntry::parse(line, device, key).filter(|_| !copied);
+            unreadable += usize::from(entry.is_none());
+            entry
+        })
+        .collect();
+    (entries, unreadable)
+}
+
+/// Everything from every device's log put together
+#[derive(Debug, Default)]
+pub struct Merged {
+    pub history: History,
+    pub config: Table,
+}
+
+/// Goes through the entries in the order they were written, ties going by device so every device
+/// ends up with the same thing
+pub fn merge(logs: Vec<(String, Vec<LogEntry>)>) -> Merged {
+    let mut entries: Vec<(u64, String, usize, Change)> = logs
+        .into_iter()
+        .flat_map(|(device, entries)| {
+            entries
+                .into_iter()
+                .enumerate()
+                .map(move |(i, entry)| (entry.at, device.clone(), i, entry.change))
+        })
+        .collect();
+    entries.sort_by(|a, b| (a.0, &a.1, a.2).cmp(&(b.0, &b.1, b.2)));
+    let mut merged = Merged::default();
+    for (_, _, _, change) in entries {
+        match change {
+            Change::Record(record) => merged.history.records.push(record),
+            Change::Config(change) => change.apply_to_table(&mut merged.config),
+        }
+    }
+    merged.history.records.sort_by_key(|record| record.at);
+    merged
+}
@@ -0,0 +1,159 @@
+//! What a team sync host and the clients mirroring its timer send each other, one message per line
+//! with tabs between the fields
+
+use std::{
+    fmt,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::phase::PomodoroPhase;
+
+/// Milliseconds since the unix epoch, which is how times are sent
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// How far the host's clock is ahead of this one, from a ping sent at `sent` that came back at
+/// `received`, taking it to have been as long on the way there as on the way back
+pub fn clock_offset(sent: u64, host: u64, received: u64) -> i64 {
+    host as i64 - ((sent + received) / 2) as i64
+}
+
+/// A phase as it's sent over the network, as instants only mean something on one machine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhaseState {
+    pub work: bool,
+    pub length: Option<Duration>,
+    pub elapsed: Duration,
+    pub paused: bool,
+}
+
+impl PhaseState {
+    pub fn new(phase: &PomodoroPhase, now: Instant) -> Self {
+        Self {
+            work: matches!(phase, PomodoroPhase::Work { .. }),
+            length: phase.get_duration(),
+            elapsed: phase.time_elapsed(now).unwrap_or_default(),
+            paused: phase.is_paused(),
+        }
+    }
+
+    /// The same phase here, moved on by `delay` if it's running, for how long it took to arrive
+    pub fn to_phase(&self, now: Instant, delay: Duration) -> PomodoroPhase {
+        let (start, paused) = if self.paused {
+            (None, Some(self.elapsed))
+        } else {
+            (now.checked_sub(self.elapsed + delay).or(Some(now)), None)
+        };
+        let length = self.length;
+        if self.work {
+            PomodoroPhase::Work {
+                start,
+                length,
+                paused,
+            }
+        } else {
+            PomodoroPhase::Break {
+                start,
+                length,
+                paused,
+            }
+        }
+    }
+
+    /// If it's a different phase, or paused or started, rather than only further along
+    pub fn differs_from(&self, other: &PhaseState) -> bool {
+        self.work != other.work || self.length != other.length || self.paused != other.paused
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// A client joining, with the name to show it as
+    Hello(String),
+    /// Everyone in the session, sent by the host whenever someone joins or leaves
+    Peers(Vec<String>),
+    /// The host's phase, as of `at` on the host's clock
+    State { state: PhaseState, at: u64 },
+    /// Sent by clients to find out how far apart the clocks are
+    Ping(u64),
+    /// The host's answer to a ping, with the time the ping was sent and the time on the host
+    Pong { sent: u64, host: u64 },
+}
+
+impl Message {
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.trim_end_matches(['\r', '\n']).split('\t');
+        let message = match fields.next()? {
+            "hello" => Self::Hello(fields.next()?.to_string()),
+            "peers" => Self::Peers(fields.map(str::to_string).collect()),
+            "state" => {
+                let work = match fields.next()? {
+                    "work" => true,
+                    "break" => false,
+                    _ => return None,
+                };
+                let length = match fields.next()? {
+                    "open" => None,
+                    ms => Some(Duration::from_millis(ms.parse().ok()?)),
+                };
+                let elapsed = Duration::from_millis(fields.next()?.parse().ok()?);
+                let paused = match fields.next()? {
+                    "paused" => true,
+                    "running" => false,
+                    _ => return None,
+                };
+                let at = fields.next()?.parse().ok()?;
+                let state = PhaseState {
+                    work,
+                    length,
+                    elapsed,
+                    paused,
+                };
+                Self::State { state, at }
+            }
+            "ping" => Self::Ping(fields.next()?.parse().ok()?),
+            "pong" => Self::Pong {
+                sent: fields.next()?.parse().ok()?,
+                host: fields.next()?.parse().ok()?,
+            },
+            _ => return None,
+        };
+        Some(message)
+    }
+}
+
+/// Names can't have the characters the messages are split on
+fn clean(name: &str) -> String {
+    name.replace(['\t', '\r', '\n'], " ")
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hello(name) => write!(f, "hello\t{}", clean(name)),
+            Self::Peers(names) => {
+                write!(f, "peers")?;
+                for name in names {
+                    write!(f, "\t{}", clean(name))?;
+                }
+                Ok(())
+            }
+            Self::State { state, at } => write!(
+                f,
+                "state\t{}\t{}\t{}\t{}\t{}",
+                if state.work { "work" } else { "break" },
+                state
+                    .length
+                    .map_or("open".to_string(), |l| l.as_millis().to_string()),
+                state.elapsed.as_millis(),
+                if state.paused { "paused" } else { "running" },
+                at
+            ),
+            Self::Ping(sent) => write!(f, "ping\t{sent}"),
+            Self::Pong { sent, host } => write!(f, "pong\t{sent}\t{host}"),
+        }
+    }
+}
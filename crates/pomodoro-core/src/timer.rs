@@ -0,0 +1,206 @@
+use std::time::Duration;
+
+use crate::{
+    clock::{Clock, SystemClock},
+    config::{Config, Step},
+    events::{Event, EventKind},
+    phase::PomodoroPhase,
+    stats::Stats,
+    utils::Percent,
+};
+
+/// The current phase and how many have been done, going through the work/break cycle
+pub struct Timer<C: Clock = SystemClock> {
+    clock: C,
+    phase: PomodoroPhase,
+    stats: Stats,
+    /// The step of the config's sequence the phase comes from, and where it is in it
+    step: Option<(usize, Step)>,
+    /// If the current phase has been started before, to tell starting and resuming apart
+    started: bool,
+    events: Vec<Event>,
+}
+
+impl Timer {
+    pub fn new(config: &Config) -> Self {
+        Self::with_clock(SystemClock, config)
+    }
+}
+
+impl<C: Clock> Timer<C> {
+    pub fn with_clock(clock: C, config: &Config) -> Self {
+        let mut timer = Self {
+            clock,
+            phase: config.new_work_phase(),
+            stats: Stats::default(),
+            step: None,
+            started: false,
+            events: vec![],
+        };
+        timer.jump_to(0, config);
+        timer
+    }
+
+    pub fn get_phase(&self) -> &PomodoroPhase {
+        &self.phase
+    }
+
+    pub fn get_stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn get_step(&self) -> Option<(usize, &Step)> {
+        self.step.as_ref().map(|(i, step)| (*i, step))
+    }
+
+    /// Goes to the step of the sequence, paused, returning false if there is no such step
+    pub fn jump_to(&mut self, i: usize, config: &Config) -> bool {
+        let Some(step) = config.get_sequence().and_then(|s| s.get(i)) else {
+            return false;
+        };
+        self.phase = step.to_phase();
+        self.step = Some((i, step.clone()));
+        self.started = false;
+        true
+    }
+
+    pub fn start(&mut self) {
+        if !self.phase.is_paused() {
+            return;
+        }
+        let kind = match (&self.phase, self.started) {
+            (_, true) => EventKind::Resume,
+            (PomodoroPhase::Work { .. }, false) => EventKind::WorkStart,
+            (PomodoroPhase::Break { .. }, false) => EventKind::BreakStart,
+        };
+        self.phase.start(self.clock.now());
+        self.started = true;
+        self.push_event(kind);
+    }
+
+    pub fn pause(&mut self) {
+        if self.phase.is_paused() {
+            return;
+        }
+        self.phase.pause(self.clock.now());
+        self.push_event(EventKind::Pause);
+    }
+
+    pub fn toggle(&mut self) {
+        if self.phase.is_paused() {
+            self.start();
+        } else {
+            self.pause();
+        }
+    }
+
+    /// Everything that happened since this was last called, oldest first
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn push_event(&mut self, kind: EventKind) {
+        // open ended phases are as long as they have gone on for
+        let duration = self
+            .phase
+            .get_duration()
+            .unwrap_or_else(|| self.time_elapsed().unwrap_or_default());
+        self.events.push(Event {
+            kind,
+            phase: self.phase.get_name(),
+            duration,
+            cycle: self.stats.get_count() + 1,
+        });
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.phase.is_paused()
+    }
+
+    pub fn time_elapsed(&self) -> Option<Duration> {
+        self.phase.time_elapsed(self.clock.now())
+    }
+
+    pub fn get_time_left(&self) -> Duration {
+        self.phase.get_time_left(self.clock.now())
+    }
+
+    pub fn to_percent(&self) -> Option<Percent> {
+        self.phase.to_percent(self.clock.now())
+    }
+
+    /// Like `to_percent`, but only changes once every second
+    pub fn to_stepped_percent(&self) -> Option<Percent> {
+        self.phase.to_stepped_percent(self.clock.now())
+    }
+
+    /// If the phase is running and has gone on for longer than it's length
+    pub fn is_done(&self) -> bool {
+        self.phase.is_done(self.clock.now())
+    }
+
+    /// Moves on to the next phase in the cycle, returning the one that ended
+    pub fn next_phase(&mut self, config: &Config) -> PomodoroPhase {
+        self.push_event(match self.phase {
+            PomodoroPhase::Work { .. } => EventKind::WorkEnd,
+            PomodoroPhase::Break { .. } => EventKind::BreakEnd,
+        });
+        let next = match self.phase {
+            PomodoroPhase::Work { .. } => {
+                let worked = self.time_elapsed().unwrap_or_default();
+                PomodoroPhase::new_break(config.get_break_time(worked))
+            }
+            PomodoroPhase::Break { .. } => config.new_work_phase(),
+        };
+        self.stats.increment();
+        let finished = std::mem::replace(&mut self.phase, next);
+        // sequences decide the next phase themselves
+        let next_step = self.step.as_ref().map_or(0, |(i, _)| i + 1);
+        let len = config.get_sequence().map_or(0, |s| s.len());
+        if !self.jump_to(next_step % len.max(1), config) {
+            self.step = None;
+        }
+        self.started = false;
+        finished
+    }
+
+    /// Takes on a phase from somewhere else, like a team sync host, without any events
+    pub fn mirror(&mut self, phase: PomodoroPhase) {
+        self.phase = phase;
+        self.step = None;
+        // so starting it from here counts as resuming
+        self.started = true;
+    }
+
+    /// Cuts the phase down to `max` if it's longer, as long as it hasn't been started yet
+    pub fn shorten_to(&mut self, max: Duration) -> bool {
+        let longer = self.phase.get_duration().is_some_and(|length| length > max);
+        if self.started || !longer {
+            return false;
+        }
+        // only the length, so it's still the same step
+        match &mut self.phase {
+            PomodoroPhase::Work { length, .. } | PomodoroPhase::Break { length, .. } => {
+                *length = Some(max);
+            }
+        }
+        true
+    }
+
+    /// Starts the current phase over, paused, picking up changes to the config
+    pub fn reset(&mut self, config: &Config) {
+        if let Some((i, _)) = self.step {
+            if self.jump_to(i, config) {
+                return;
+            }
+        }
+        self.step = None;
+        self.phase = match self.phase {
+            PomodoroPhase::Work { .. } => config.new_work_phase(),
+            PomodoroPhase::Break { length, .. } => {
+                PomodoroPhase::new_break(length.unwrap_or(config.break_time))
+            }
+        };
+        self.started = false;
+    }
+}
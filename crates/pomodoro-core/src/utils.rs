@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use subtle::ConstantTimeEq;
+
+/// Formats a duration as `mm:ss`
+pub fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Reads lengths like `25m`, `90s` or `1h30m`, where a plain number is in minutes
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    if let Ok(minutes) = s.parse::<f64>() {
+        // too long to fit in a duration is treated as not a length
+        return Duration::try_from_secs_f64(minutes * 60.0).ok();
+    }
+    if s.is_empty() {
+        return None;
+    }
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 60.0 * 60.0,
+            'm' => 60.0,
+            's' => 1.0,
+            _ => return None,
+        };
+        seconds += number.parse::<f64>().ok()? * unit;
+        number.clear();
+    }
+    // a number without a unit after it
+    if !number.is_empty() {
+        return None;
+    }
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+pub struct Percent {
+    percent: f32,
+}
+
+impl Percent {
+    pub fn new(n: f32) -> Result<Self, String> {
+        // this percentage cannot go to 0 because then the indexing will break in the get_points
+        // method
+        if !(0.0..=100.0).contains(&n) {
+            return Err("Out of bounds".to_owned());
+        }
+        Ok(Self { percent: n })
+    }
+
+    pub fn map_to_value<T: Into<f64> + From<f64>>(&self, value: T) -> T {
+        let value: f64 = value.into();
+        let x = (self.percent as f64 / 100.0) * (value);
+        // clamps it because it could go over the value
+        x.clamp(0.0, value).into()
+    }
+}
+
+/// Compared in constant time, so how long it takes doesn't give away how much of it was right
+pub fn is_token(given: &str, token: &str) -> bool {
+    given.as_bytes().ct_eq(token.as_bytes()).into()
+}
@@ -0,0 +1,77 @@
+use std::{io::Cursor, time::Duration};
+
+use pomodoro_core::{
+    api::{ConfigUpdate, Request, Status},
+    config::Mode,
+};
+
+fn read(raw: &str) -> Option<Request> {
+    Request::read(&mut Cursor::new(raw.as_bytes()))
+}
+
+#[test]
+fn requests_are_read_with_their_body() {
+    let request = read(
+        "PUT /config?x=1 HTTP/1.1\r\nAuthorization: Bearer secret\r\nContent-Length: 19\r\n\r\n{\"work_minutes\":50}",
+    )
+    .unwrap();
+    assert_eq!(request.method, "PUT");
+    assert_eq!(request.path, "/config");
+    assert_eq!(request.query, [("x".to_string(), "1".to_string())]);
+    assert_eq!(request.body, "{\"work_minutes\":50}");
+    assert!(request.is_authorized(Some("secret")));
+    assert!(!request.is_authorized(Some("guess")));
+
+    assert!(read("hello there\r\n").is_none());
+}
+
+#[test]
+fn tokens_can_be_in_the_query() {
+    let request = read("GET /events?token=secret HTTP/1.1\r\n\r\n").unwrap();
+    assert!(request.is_authorized(Some("secret")));
+    let request = read("GET /events HTTP/1.1\r\n\r\n").unwrap();
+    assert!(!request.is_authorized(Some("secret")));
+    assert!(request.is_authorized(None));
+}
+
+#[test]
+fn only_the_event_stream_takes_the_token_in_the_query() {
+    let request = read("POST /toggle?token=secret HTTP/1.1\r\n\r\n").unwrap();
+    assert!(!request.is_authorized(Some("secret")));
+    let request = read("GET /status?token=secret HTTP/1.1\r\n\r\n").unwrap();
+    assert!(!request.is_authorized(Some("secret")));
+    let request = read("GET /events?token=secre HTTP/1.1\r\n\r\n").unwrap();
+    assert!(!request.is_authorized(Some("secret")));
+}
+
+#[test]
+fn config_updates_take_minutes() {
+    let update = ConfigUpdate::parse("{ \"work_minutes\": 50, \"break_minutes\": 7.5 }").unwrap();
+    assert_eq!(update.work_time, Some(Duration::from_secs(50 * 60)));
+    assert_eq!(update.break_time, Some(Duration::from_secs(450)));
+    let update = ConfigUpdate::parse("{\"work_minutes\": 0.5, \"break_minutes\": 0.0251}").unwrap();
+    assert_eq!(update.work_time, Some(Duration::from_secs(30)));
+    assert_eq!(update.break_time, Some(Duration::from_secs(2)));
+    assert_eq!(ConfigUpdate::parse("{}"), Ok(ConfigUpdate::default()));
+    assert!(ConfigUpdate::parse("{\"work_minutes\": -5}").is_err());
+    assert!(ConfigUpdate::parse("{\"work_minutes\": 0}").is_err());
+    assert!(ConfigUpdate::parse("{\"break_minutes\": 0.001}").is_err());
+    assert!(ConfigUpdate::parse("{\"theme\": 1}").is_err());
+    assert!(ConfigUpdate::parse("work_minutes=5").is_err());
+}
+
+#[test]
+fn status_is_json() {
+    let status = Status {
+        mode: Mode::Pomodoro,
+        name: "Deep \"work\"".to_string(),
+        paused: false,
+        time: Duration::from_secs(90),
+        counts_up: false,
+        cycles: 2,
+    };
+    assert_eq!(
+        status.to_json(),
+        r#"{"mode":"pomodoro","name":"Deep \"work\"","paused":false,"time":90,"counts_up":false,"cycles":2}"#
+    );
+}
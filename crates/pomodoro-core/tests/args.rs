@@ -0,0 +1,144 @@
+use std::{path::PathBuf, time::Duration};
+
+use pomodoro_core::{
+    args::{Args, Overrides, Subcommand},
+    instance::Command,
+    utils::parse_duration,
+    Config,
+};
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+fn parse(args: &[&str]) -> Result<Args, String> {
+    Args::parse(args.iter().map(|a| a.to_string()))
+}
+
+#[test]
+fn lengths_can_be_written_a_few_ways() {
+    assert_eq!(parse_duration("25"), Some(25 * MINUTE));
+    assert_eq!(parse_duration("25m"), Some(25 * MINUTE));
+    assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+    assert_eq!(parse_duration("1h30m"), Some(90 * MINUTE));
+    assert_eq!(parse_duration("1.5h"), Some(90 * MINUTE));
+    assert_eq!(parse_duration("1h30"), None);
+    assert_eq!(parse_duration("5 minutes"), None);
+    assert_eq!(parse_duration(""), None);
+    assert_eq!(parse_duration("-5"), None);
+    // too long to fit
+    assert_eq!(parse_duration("1e20"), None);
+    assert_eq!(parse_duration("99999999999999999999h"), None);
+}
+
+#[test]
+fn arguments_are_read() {
+    let args = parse(&[
+        "--config",
+        "pomodoro.toml",
+        "--work=50m",
+        "--break",
+        "10m",
+        "--start",
+        "--compact",
+        "--no-sound",
+    ])
+    .unwrap();
+    assert_eq!(args.config, Some(PathBuf::from("pomodoro.toml")));
+    assert_eq!(
+        args.overrides,
+        Overrides {
+            work_time: Some(50 * MINUTE),
+            break_time: Some(10 * MINUTE),
+            profile: None,
+            sound: Some(false),
+        }
+    );
+    assert_eq!(args.commands, vec![Command::Start]);
+    assert!(args.compact && !args.save);
+    assert!(args.has_launch_options());
+
+    let args = parse(&["--profile", "deep", "--toggle", "--skip"]).unwrap();
+    assert_eq!(
+        args.get_forwarded(),
+        vec![
+            Command::Profile("deep".to_string()),
+            Command::Toggle,
+            Command::Skip
+        ]
+    );
+    assert!(!args.has_launch_options());
+    assert_eq!(
+        parse(&["stats"]).unwrap().subcommand,
+        Some(Subcommand::Stats)
+    );
+}
+
+#[test]
+fn bad_arguments_are_errors() {
+    assert!(parse(&["--work"]).is_err());
+    assert!(parse(&["--work", "soon"]).is_err());
+    assert!(parse(&["--break", "0m"]).is_err());
+    assert!(parse(&["--work", "1e20"]).is_err());
+    assert!(parse(&["--work", "99999999999999999999h"]).is_err());
+    assert!(parse(&["--start=yes"]).is_err());
+    assert!(parse(&["--pause"]).is_err());
+    assert!(parse(&["status", "export"]).is_err());
+}
+
+#[test]
+fn overrides_are_left_out_when_saving() {
+    let mut config = Config::default();
+    let overrides = Overrides {
+        work_time: Some(50 * MINUTE),
+        sound: Some(false),
+        ..Default::default()
+    };
+    let applied = overrides.apply(&mut config).unwrap();
+    assert_eq!(config.work_time, 50 * MINUTE);
+    assert!(!config.sound);
+
+    let mut saved = config.clone();
+    applied.revert(&mut saved);
+    assert_eq!(saved.work_time, Config::default().work_time);
+    assert!(saved.sound);
+
+    // changed in the window afterwards, so that's kept
+    config.work_time = 40 * MINUTE;
+    applied.revert(&mut config);
+    assert_eq!(config.work_time, 40 * MINUTE);
+    assert!(config.sound);
+}
+
+#[test]
+fn lengths_under_a_minute_are_left_out_when_saving() {
+    let saved = Config::default();
+    let mut config = saved.clone();
+    let overrides = Overrides {
+        work_time: Some(Duration::from_secs(30)),
+        break_time: Some(Duration::from_secs(90)),
+        ..Default::default()
+    };
+    let applied = overrides.apply(&mut config).unwrap();
+    config.validate().unwrap();
+
+    // nothing was changed in the window, so what gets written is what was loaded
+    applied.revert(&mut config);
+    assert_eq!(config.work_time, saved.work_time);
+    assert_eq!(config.break_time, saved.break_time);
+    assert_eq!(toml::to_string(&config), toml::to_string(&saved));
+}
+
+#[test]
+fn overriding_an_unknown_profile_fails() {
+    let mut config = Config::default();
+    let overrides = Overrides {
+        profile: Some("nope".to_string()),
+        ..Default::default()
+    };
+    assert!(overrides.apply(&mut config).is_err());
+    let overrides = Overrides {
+        profile: Some("deep".to_string()),
+        ..Default::default()
+    };
+    overrides.apply(&mut config).unwrap();
+    assert_eq!(config.work_time, 50 * MINUTE);
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use pomodoro_core::config::{Config, EyeReminders, Flowtime, TimerConfig};
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+#[test]
+fn eye_reminders_are_due_every_interval() {
+    let reminders = EyeReminders {
+        enabled: true,
+        ..Default::default()
+    };
+    assert!(!reminders.is_due(19 * MINUTE, 0));
+    assert!(reminders.is_due(20 * MINUTE, 0));
+    assert!(!reminders.is_due(30 * MINUTE, 1));
+    assert!(reminders.is_due(40 * MINUTE, 1));
+}
+
+#[test]
+fn eye_reminders_are_off_by_default() {
+    assert!(!EyeReminders::default().is_due(60 * MINUTE, 0));
+}
+
+#[test]
+fn flowtime_breaks_come_from_the_tiers() {
+    let flowtime = Flowtime::default();
+    assert_eq!(flowtime.get_break_time(10 * MINUTE), 5 * MINUTE);
+    assert_eq!(flowtime.get_break_time(25 * MINUTE), 5 * MINUTE);
+    assert_eq!(flowtime.get_break_time(40 * MINUTE), 8 * MINUTE);
+    assert_eq!(flowtime.get_break_time(200 * MINUTE), 10 * MINUTE);
+}
+
+#[test]
+fn flowtime_breaks_use_the_ratio_without_tiers() {
+    let flowtime = Flowtime {
+        ratio: 5.0,
+        tiers: vec![],
+    };
+    assert_eq!(flowtime.get_break_time(40 * MINUTE), 8 * MINUTE);
+}
+
+#[test]
+fn zero_lengths_are_not_valid() {
+    assert!(Config::default().validate().is_ok());
+    let config = Config {
+        countdown_time: Duration::ZERO,
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+    let config = Config {
+        timers: vec![TimerConfig {
+            name: "Tea".to_string(),
+            length: Duration::ZERO,
+            color: None,
+            sound: None,
+        }],
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn working_hours_have_to_end_after_they_start() {
+    let mut config = Config::default();
+    config.schedule.hours[0].end = config.schedule.hours[0].start;
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn mqtt_passwords_need_a_username() {
+    let mut config = Config::default();
+    config.mqtt.password = Some("secret".to_string());
+    assert!(config.validate().is_err());
+    config.mqtt.username = Some("me".to_string());
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn flowtime_breaks_are_never_empty() {
+    let flowtime = Flowtime {
+        ratio: 5.0,
+        tiers: vec![],
+    };
+    assert_eq!(
+        flowtime.get_break_time(Duration::ZERO),
+        Duration::from_secs(1)
+    );
+    let config = Config {
+        flowtime: Flowtime {
+            ratio: 0.0,
+            tiers: vec![],
+        },
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
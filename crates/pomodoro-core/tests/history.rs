@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use pomodoro_core::history::{Entry, History, Record};
+
+fn pomodoro(at: u64, phase: &str, skipped: bool) -> Record {
+    Record {
+        at,
+        entry: Entry::Pomodoro {
+            phase: phase.to_string(),
+            length: 25 * 60,
+            skipped,
+        },
+    }
+}
+
+#[test]
+fn only_finished_work_counts_as_completed() {
+    let history = History {
+        records: vec![
+            pomodoro(50, "Work", false),
+            pomodoro(100, "Work", false),
+            pomodoro(200, "Break", false),
+            pomodoro(300, "Work", true),
+            Record {
+                at: 400,
+                entry: Entry::Countdown { length: 60 },
+            },
+            pomodoro(500, "Work", false),
+        ],
+    };
+    assert_eq!(history.completed_work_since(100), 2);
+    assert_eq!(history.completed_work_since(0), 3);
+    assert_eq!(history.completed_work_since(600), 0);
+}
+
+#[test]
+fn summaries_add_up_the_work() {
+    let history = History {
+        records: vec![
+            pomodoro(100, "Work", false),
+            pomodoro(200, "Break", false),
+            pomodoro(300, "Work", true),
+        ],
+    };
+    let summary = history.summarize(0);
+    assert_eq!((summary.completed, summary.skipped), (1, 1));
+    assert_eq!(summary.worked, Duration::from_secs(2 * 25 * 60));
+}
+
+#[test]
+fn exports_one_row_per_record() {
+    let history = History {
+        records: vec![
+            pomodoro(100, "Write, then edit", false),
+            Record {
+                at: 200,
+                entry: Entry::Stopwatch {
+                    elapsed: 90,
+                    laps: vec![60, 30],
+                },
+            },
+        ],
+    };
+    assert_eq!(
+        history.to_csv(),
+        "at,kind,phase,length,skipped,acknowledged,laps\n\
+         100,pomodoro,\"Write, then edit\",1500,false,,\n\
+         200,stopwatch,,90,,,60;30\n"
+    );
+}
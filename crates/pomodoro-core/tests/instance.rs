@@ -0,0 +1,42 @@
+use std::path::Path;
+
+use pomodoro_core::instance::{get_name, Command, PortFile};
+
+#[test]
+fn commands_survive_being_forwarded() {
+    for command in [
+        Command::Show,
+        Command::Start,
+        Command::Toggle,
+        Command::Skip,
+        Command::Profile("deep work".to_string()),
+        Command::Status,
+    ] {
+        assert_eq!(Command::parse(&format!("{command}\n")), Some(command));
+    }
+    assert_eq!(Command::parse("profile\t"), None);
+    assert_eq!(Command::parse("quit"), None);
+}
+
+#[test]
+fn port_files_have_the_token() {
+    let port_file = PortFile {
+        port: 43210,
+        token: "0123abcd".to_string(),
+    };
+    assert_eq!(PortFile::parse(&format!("{port_file}\n")), Some(port_file));
+    // from before there was a token
+    assert_eq!(PortFile::parse("43210"), None);
+    assert_eq!(PortFile::parse("43210\t"), None);
+}
+
+#[test]
+fn each_config_gets_its_own_instance() {
+    let name = get_name(Path::new("/home/me/.config/Pomodoro/config.toml"));
+    assert_eq!(
+        name,
+        get_name(Path::new("/home/me/.config/Pomodoro/config.toml"))
+    );
+    assert_ne!(name, get_name(Path::new("/home/me/work.toml")));
+    assert!(name.starts_with("instance-"));
+}
@@ -0,0 +1,130 @@
+use std::{
+    io::{Cursor, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use pomodoro_core::mqtt::{self, Packet};
+
+fn read(bytes: &[u8]) -> Packet {
+    mqtt::read_packet(&mut Cursor::new(bytes)).unwrap()
+}
+
+#[test]
+fn connect_asks_for_a_clean_session() {
+    let packet = mqtt::connect("pomodoro", None, None, 30).unwrap();
+    assert_eq!(
+        packet,
+        [
+            &[0x10, 20, 0, 4][..],
+            b"MQTT",
+            &[4, 0x02, 0, 30, 0, 8],
+            b"pomodoro"
+        ]
+        .concat()
+    );
+    let with_login = mqtt::connect("p", Some("user"), Some("pass"), 30).unwrap();
+    assert_eq!(with_login[9], 0x02 | 0x80 | 0x40);
+    assert!(with_login.ends_with(b"\0\x04user\0\x04pass"));
+    // not allowed without a username, so it's left out
+    let password_only = mqtt::connect("p", None, Some("pass"), 30).unwrap();
+    assert_eq!(password_only, mqtt::connect("p", None, None, 30).unwrap());
+}
+
+#[test]
+fn strings_too_long_to_send_are_errors() {
+    let long = "x".repeat(usize::from(u16::MAX) + 1);
+    assert!(mqtt::connect(&long, None, None, 30).is_err());
+    assert!(mqtt::publish(&long, b"Work", true).is_err());
+    assert!(mqtt::subscribe(1, &long).is_err());
+    assert!(mqtt::publish(&long[1..], b"Work", true).is_ok());
+}
+
+#[test]
+fn publishes_can_be_read_back() {
+    let packet = mqtt::publish("pomodoro/phase", b"Work", true).unwrap();
+    // retained
+    assert_eq!(packet[0], 0x31);
+    assert_eq!(
+        read(&packet),
+        Packet::Publish {
+            topic: "pomodoro/phase".to_string(),
+            payload: b"Work".to_vec()
+        }
+    );
+
+    // long enough to need two bytes for the length
+    let payload = vec![b'x'; 200];
+    let packet = mqtt::publish("t", &payload, false).unwrap();
+    assert_eq!(&packet[..3], [0x30, (203 % 128) | 0x80, 1]);
+    assert_eq!(
+        read(&packet),
+        Packet::Publish {
+            topic: "t".to_string(),
+            payload
+        }
+    );
+}
+
+#[test]
+fn packet_ids_are_skipped_over() {
+    // a QoS 1 publish from the broker, with packet id 7
+    let packet = [0x32, 9, 0, 1, b't', 0, 7, b's', b'k', b'i', b'p'];
+    assert_eq!(
+        read(&packet),
+        Packet::Publish {
+            topic: "t".to_string(),
+            payload: b"skip".to_vec()
+        }
+    );
+}
+
+#[test]
+fn broker_replies_are_recognised() {
+    assert_eq!(read(&[0x20, 2, 0, 0]), Packet::ConnAck { code: 0 });
+    assert_eq!(read(&[0x20, 2, 0, 5]), Packet::ConnAck { code: 5 });
+    assert_eq!(read(&[0x90, 3, 0, 1, 0]), Packet::SubAck);
+    assert_eq!(read(&[0xD0, 0]), Packet::PingResp);
+    assert!(mqtt::read_packet(&mut Cursor::new([0x30, 5, 0])).is_err());
+    assert_eq!(
+        mqtt::subscribe(1, "a/b").unwrap(),
+        [0x82, 8, 0, 1, 0, 3, b'a', b'/', b'b', 0]
+    );
+}
+
+/// Needs a broker, like `mosquitto`, running on the usual port
+#[test]
+#[ignore]
+fn round_trip_through_a_local_broker() {
+    let mut stream = TcpStream::connect(("localhost", 1883)).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let mut reader = stream.try_clone().unwrap();
+    let topic = format!("pomodoro-test/{}", std::process::id());
+
+    stream
+        .write_all(&mqtt::connect("pomodoro-test", None, None, 30).unwrap())
+        .unwrap();
+    assert_eq!(
+        mqtt::read_packet(&mut reader).unwrap(),
+        Packet::ConnAck { code: 0 }
+    );
+    stream
+        .write_all(&mqtt::subscribe(1, &topic).unwrap())
+        .unwrap();
+    assert_eq!(mqtt::read_packet(&mut reader).unwrap(), Packet::SubAck);
+    stream
+        .write_all(&mqtt::publish(&topic, b"Work", false).unwrap())
+        .unwrap();
+    assert_eq!(
+        mqtt::read_packet(&mut reader).unwrap(),
+        Packet::Publish {
+            topic,
+            payload: b"Work".to_vec()
+        }
+    );
+    stream.write_all(&mqtt::ping()).unwrap();
+    assert_eq!(mqtt::read_packet(&mut reader).unwrap(), Packet::PingResp);
+    stream.write_all(&mqtt::disconnect()).unwrap();
+}
@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+use pomodoro_core::{format_time, PomodoroPhase};
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+#[test]
+fn new_phase_is_paused_at_zero() {
+    let phase = PomodoroPhase::new_work(25 * MINUTE);
+    let now = Instant::now();
+    assert!(phase.is_paused());
+    assert_eq!(phase.get_start(), None);
+    assert_eq!(phase.time_elapsed(now), Some(Duration::ZERO));
+    assert_eq!(phase.get_time_left(now), 25 * MINUTE);
+}
+
+#[test]
+fn pause_keeps_elapsed_time() {
+    let mut phase = PomodoroPhase::new_work(25 * MINUTE);
+    let start = Instant::now();
+    phase.start(start);
+    phase.pause(start + 3 * MINUTE);
+
+    // time passing while paused doesn't count
+    let later = start + 60 * MINUTE;
+    assert!(phase.is_paused());
+    assert_eq!(phase.time_elapsed(later), Some(3 * MINUTE));
+
+    // resuming carries on from where it was paused
+    phase.start(later);
+    assert_eq!(phase.time_elapsed(later + MINUTE), Some(4 * MINUTE));
+    assert_eq!(phase.get_time_left(later + MINUTE), 21 * MINUTE);
+}
+
+#[test]
+fn toggle_switches_between_running_and_paused() {
+    let mut phase = PomodoroPhase::new_break(5 * MINUTE);
+    let now = Instant::now();
+    phase.toggle(now);
+    assert!(!phase.is_paused());
+    phase.toggle(now + MINUTE);
+    assert!(phase.is_paused());
+    assert_eq!(phase.time_elapsed(now + 2 * MINUTE), Some(MINUTE));
+}
+
+#[test]
+fn time_left_does_not_go_below_zero() {
+    let mut phase = PomodoroPhase::new_break(5 * MINUTE);
+    let now = Instant::now();
+    phase.start(now);
+    assert_eq!(phase.get_time_left(now + 10 * MINUTE), Duration::ZERO);
+}
+
+#[test]
+fn percent_counts_down() {
+    let mut phase = PomodoroPhase::new_work(10 * MINUTE);
+    let now = Instant::now();
+    assert_eq!(phase.to_percent(now).unwrap().map_to_value(100.0), 100.0);
+
+    phase.start(now);
+    let percent = phase.to_percent(now + 2 * MINUTE + Duration::from_millis(500));
+    assert!((percent.unwrap().map_to_value(100.0_f64) - 79.916).abs() < 0.01);
+    let percent = phase.to_percent(now + 20 * MINUTE);
+    assert_eq!(percent.unwrap().map_to_value(100.0), 0.0);
+}
+
+#[test]
+fn zero_length_phases_are_empty() {
+    let phase = PomodoroPhase::new_work(Duration::ZERO);
+    let now = Instant::now();
+    assert_eq!(phase.to_percent(now).unwrap().map_to_value(100.0), 0.0);
+    assert_eq!(
+        phase.to_stepped_percent(now).unwrap().map_to_value(100.0),
+        0.0
+    );
+}
+
+#[test]
+fn stepped_percent_only_changes_every_second() {
+    let mut phase = PomodoroPhase::new_work(Duration::from_secs(100));
+    let now = Instant::now();
+    phase.start(now);
+    let percent = phase.to_stepped_percent(now + Duration::from_millis(1999));
+    assert_eq!(percent.unwrap().map_to_value(100.0), 99.0);
+}
+
+#[test]
+fn time_is_formatted_as_minutes_and_seconds() {
+    assert_eq!(format_time(Duration::from_secs(0)), "00:00");
+    assert_eq!(format_time(Duration::from_secs(25 * 60 + 7)), "25:07");
+    assert_eq!(format_time(Duration::from_secs(120 * 60)), "120:00");
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use pomodoro_core::schedule::{LocalTime, Schedule, TimeOfDay, Weekday};
+
+fn at(day: Weekday, hour: u16, minute: u16) -> LocalTime {
+    LocalTime {
+        day,
+        time: TimeOfDay::new(hour, minute).unwrap(),
+        second: 0,
+    }
+}
+
+#[test]
+fn lunch_is_outside_working_hours() {
+    let schedule = Schedule::default();
+    assert!(!schedule.is_working_hours(at(Weekday::Mon, 8, 59)));
+    assert!(schedule.is_working_hours(at(Weekday::Mon, 9, 0)));
+    assert!(!schedule.is_working_hours(at(Weekday::Mon, 12, 30)));
+    assert!(!schedule.is_working_hours(at(Weekday::Sat, 10, 0)));
+    assert_eq!(
+        schedule.time_left(at(Weekday::Tue, 17, 15)),
+        Some(Duration::from_secs(45 * 60))
+    );
+    let nearly_done = LocalTime {
+        second: 30,
+        ..at(Weekday::Tue, 17, 59)
+    };
+    assert_eq!(
+        schedule.time_left(nearly_done),
+        Some(Duration::from_secs(30))
+    );
+}
+
+#[test]
+fn next_session_wraps_round_the_week() {
+    let schedule = Schedule::default();
+    assert_eq!(
+        schedule.next_session(at(Weekday::Mon, 12, 30)),
+        Some(at(Weekday::Mon, 13, 0))
+    );
+    assert_eq!(
+        schedule.next_session(at(Weekday::Fri, 18, 0)),
+        Some(at(Weekday::Mon, 9, 0))
+    );
+}
+
+#[test]
+fn auto_start_only_fires_once_it_is_passed() {
+    let schedule = Schedule::default();
+    assert!(schedule.auto_starts_between(at(Weekday::Wed, 8, 59), at(Weekday::Wed, 9, 0)));
+    assert!(!schedule.auto_starts_between(at(Weekday::Wed, 9, 0), at(Weekday::Wed, 9, 1)));
+    // only the morning starts the timer
+    assert!(!schedule.auto_starts_between(at(Weekday::Wed, 12, 59), at(Weekday::Wed, 13, 0)));
+    // asleep over the weekend
+    assert!(schedule.auto_starts_between(at(Weekday::Fri, 20, 0), at(Weekday::Mon, 10, 0)));
+}
+
+#[test]
+fn times_are_written_as_hours_and_minutes() {
+    let time = TimeOfDay::try_from("9:05".to_string()).unwrap();
+    assert_eq!(time.to_string(), "09:05");
+    assert!(TimeOfDay::try_from("25:00".to_string()).is_err());
+    assert!(TimeOfDay::try_from("noon".to_string()).is_err());
+}
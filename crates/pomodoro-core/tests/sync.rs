@@ -0,0 +1,305 @@
+use std::{path::PathBuf, time::Duration};
+
+use pomodoro_core::{
+    config::{DndBackend, TeamSyncMode, ThemeMode},
+    crypto::{Cost, Key},
+    sync::{
+        get_setting, merge, new_key_file, open_key_file, parse_log, synced_table, Change,
+        ConfigChange, LogEntry,
+    },
+    Config, Entry, Record,
+};
+use toml::Table;
+
+/// Cheap enough that the tests don't take long
+const COST: Cost = Cost {
+    memory: 8,
+    passes: 1,
+};
+
+fn key(passphrase: &str) -> Key {
+    Key::derive(passphrase, b"saltsalt", COST).unwrap()
+}
+
+fn record(at: u64, phase: &str) -> LogEntry {
+    LogEntry {
+        at: at * 1000,
+        change: Change::Record(Record {
+            at,
+            entry: Entry::Pomodoro {
+                phase: phase.to_string(),
+                length: 25 * 60,
+                skipped: false,
+            },
+        }),
+    }
+}
+
+fn config_change(at: u64, from: &Config, to: &Config) -> LogEntry {
+    LogEntry {
+        at,
+        change: Change::Config(ConfigChange::between(
+            &synced_table(from).unwrap(),
+            &synced_table(to).unwrap(),
+        )),
+    }
+}
+
+/// What a device writes the first time, when the folder's empty
+fn first_config(at: u64, config: &Config) -> LogEntry {
+    LogEntry {
+        at,
+        change: Change::Config(ConfigChange::between(
+            &Table::new(),
+            &synced_table(config).unwrap(),
+        )),
+    }
+}
+
+#[test]
+fn only_the_right_passphrase_decrypts() {
+    let key = key("correct horse");
+    let encrypted = key.encrypt([7; 12], b"Work", b"laptop");
+    assert_ne!(&encrypted[12..16], b"Work");
+    assert_eq!(key.decrypt(&encrypted, b"laptop"), Some(b"Work".to_vec()));
+
+    let mut changed = encrypted.clone();
+    changed[13] ^= 1;
+    assert_eq!(key.decrypt(&changed, b"laptop"), None);
+    assert_eq!(key.decrypt(&encrypted, b"desktop"), None);
+    assert_eq!(
+        self::key("battery staple").decrypt(&encrypted, b"laptop"),
+        None
+    );
+}
+
+#[test]
+fn the_key_file_checks_the_passphrase() {
+    let (key, contents) =
+        new_key_file("correct horse", b"0123456789abcdef", COST, [3; 12]).unwrap();
+    assert!(contents.starts_with("argon2id\t8\t1\t"));
+    let opened = open_key_file(&contents, "correct horse").unwrap();
+    assert_eq!(
+        opened.decrypt(&key.encrypt([4; 12], b"Work", b""), b""),
+        Some(b"Work".to_vec())
+    );
+    assert!(open_key_file(&contents, "battery staple").is_err());
+    assert!(open_key_file("nonsense", "correct horse").is_err());
+}
+
+#[test]
+fn log_lines_survive_being_written() {
+    let key = key("correct horse");
+    let entry = record(100, "Work");
+    let line = entry.to_line("laptop", None).unwrap();
+    assert_eq!(LogEntry::parse(&line, "laptop", None), Some(entry.clone()));
+    let line = entry.to_line("laptop", Some((&key, [1; 12]))).unwrap();
+    assert_eq!(
+        LogEntry::parse(&line, "laptop", Some(&key)),
+        Some(entry.clone())
+    );
+    assert_eq!(LogEntry::parse(&line, "laptop", None), None);
+
+    // the last line is still being written
+    let log = format!("{line}\n{}", &line[..line.len() / 2]);
+    let (entries, unreadable) = parse_log(&log, "laptop", Some(&key));
+    assert_eq!((entries.len(), unreadable), (1, 0));
+    let (entries, unreadable) = parse_log("garbage\n", "laptop", None);
+    assert_eq!((entries.len(), unreadable), (0, 1));
+}
+
+#[test]
+fn encrypted_lines_cannot_be_moved_or_copied() {
+    let key = key("correct horse");
+    let line = record(100, "Work")
+        .to_line("laptop", Some((&key, [1; 12])))
+        .unwrap();
+    // into another device's log
+    assert_eq!(LogEntry::parse(&line, "desktop", Some(&key)), None);
+    // to another time
+    let moved = line.replacen("100000", "200000", 1);
+    assert_eq!(LogEntry::parse(&moved, "laptop", Some(&key)), None);
+    // twice into the same log
+    let (entries, unreadable) = parse_log(&format!("{line}\n{line}\n"), "laptop", Some(&key));
+    assert_eq!((entries.len(), unreadable), (1, 1));
+}
+
+#[test]
+fn logs_are_merged_by_time() {
+    let merged = merge(vec![
+        (
+            "laptop".to_string(),
+            vec![record(200, "Break"), record(300, "Work")],
+        ),
+        (
+            "desktop".to_string(),
+            vec![record(100, "Work"), record(250, "Break")],
+        ),
+    ]);
+    let times: Vec<u64> = merged.history.records.iter().map(|r| r.at).collect();
+    assert_eq!(times, vec![100, 200, 250, 300]);
+}
+
+#[test]
+fn config_changes_on_different_devices_are_all_kept() {
+    let base = Config::default();
+    let mut desktop = base.clone();
+    desktop.work_time = Duration::from_secs(50 * 60);
+    desktop.profile = Some("deep".to_string());
+    let mut laptop = base.clone();
+    laptop.sound = false;
+    let mut later = desktop.clone();
+    later.profile = None;
+
+    let merged = merge(vec![
+        (
+            "desktop".to_string(),
+            vec![
+                first_config(0, &base),
+                config_change(10, &base, &desktop),
+                config_change(30, &desktop, &later),
+            ],
+        ),
+        (
+            "laptop".to_string(),
+            vec![config_change(20, &base, &laptop)],
+        ),
+    ]);
+    let change = ConfigChange::between(&synced_table(&base).unwrap(), &merged.config);
+    let mut elsewhere = Config {
+        window_size: Some([300.0, 400.0]),
+        ..Default::default()
+    };
+    elsewhere.folder_sync.folder = Some(PathBuf::from("/sync"));
+    let synced = change.apply(&elsewhere).unwrap();
+    assert_eq!(synced.work_time, Duration::from_secs(50 * 60));
+    assert!(!synced.sound);
+    assert_eq!(synced.profile, None);
+    // these stay as they were on this device
+    assert_eq!(synced.window_size, Some([300.0, 400.0]));
+    assert_eq!(synced.folder_sync.folder, Some(PathBuf::from("/sync")));
+}
+
+#[test]
+fn different_settings_in_the_same_table_are_all_kept() {
+    let base = Config::default();
+    let mut desktop = base.clone();
+    desktop.theme.mode = ThemeMode::Light;
+    desktop.theme.text_color = Some("#eeeeee".to_string());
+    let mut laptop = base.clone();
+    laptop.theme.work_color = "#123456".to_string();
+
+    // written out and read back, as it would be from the folder
+    let read = |device: &str, entries: Vec<LogEntry>| {
+        let log: String = entries
+            .iter()
+            .map(|e| e.to_line(device, None).unwrap() + "\n")
+            .collect();
+        let (entries, unreadable) = parse_log(&log, device, None);
+        assert_eq!(unreadable, 0);
+        (device.to_string(), entries)
+    };
+    let merged = merge(vec![
+        read(
+            "desktop",
+            vec![first_config(0, &base), config_change(10, &base, &desktop)],
+        ),
+        read("laptop", vec![config_change(20, &base, &laptop)]),
+    ]);
+    let synced = ConfigChange::between(&synced_table(&base).unwrap(), &merged.config)
+        .apply(&base)
+        .unwrap();
+    assert_eq!(synced.theme.mode, ThemeMode::Light);
+    assert_eq!(synced.theme.text_color.as_deref(), Some("#eeeeee"));
+    assert_eq!(synced.theme.work_color, "#123456");
+    assert_eq!(synced.theme.break_color, base.theme.break_color);
+
+    // and taking the color away again only takes away the color
+    let mut plain = desktop.clone();
+    plain.theme.text_color = None;
+    let change = ConfigChange::between(
+        &synced_table(&desktop).unwrap(),
+        &synced_table(&plain).unwrap(),
+    );
+    let synced = change.apply(&synced).unwrap();
+    assert_eq!(synced.theme.text_color, None);
+    assert_eq!(synced.theme.work_color, "#123456");
+}
+
+/// Set differently on a device, for the settings that should stay on it
+fn set_device_settings(config: &mut Config, name: &str) {
+    config.window_size = Some([300.0, 400.0]);
+    config.folder_sync.folder = Some(PathBuf::from(format!("/{name}/sync")));
+    config.hooks.on_work_start = Some(format!("notify-send {name}"));
+    config.do_not_disturb = Some(DndBackend::Mako);
+    config.http_api.enabled = true;
+    config.http_api.token = Some(name.to_string());
+    config.mqtt.client_id = name.to_string();
+    config.team_sync.mode = Some(TeamSyncMode::Host);
+    config.team_sync.address = format!("{name}:7777");
+    config.focus_guard.hosts_file = Some(PathBuf::from(format!("/{name}/hosts")));
+    config.theme.font = Some(PathBuf::from(format!("/{name}/font.ttf")));
+}
+
+#[test]
+fn device_settings_are_never_synced() {
+    let base = Config::default();
+    let mut desktop = base.clone();
+    set_device_settings(&mut desktop, "desktop");
+    desktop.sound = false;
+    desktop.mqtt.host = "broker".to_string();
+    desktop.theme.mode = ThemeMode::Light;
+
+    for change in [
+        ConfigChange::between(&Table::new(), &synced_table(&desktop).unwrap()),
+        ConfigChange::between(
+            &synced_table(&base).unwrap(),
+            &synced_table(&desktop).unwrap(),
+        ),
+    ] {
+        let mut written = Table::new();
+        change.apply_to_table(&mut written);
+        for path in [
+            "folder_sync",
+            "window_size",
+            "hooks",
+            "do_not_disturb",
+            "http_api",
+            "mqtt.client_id",
+            "team_sync.mode",
+            "team_sync.address",
+            "focus_guard.hosts_file",
+            "theme.font",
+        ] {
+            let path: Vec<String> = path.split('.').map(str::to_string).collect();
+            assert_eq!(get_setting(&written, &path), None, "{path:?} was synced");
+        }
+        assert!(change.touches(&["sound".to_string()]));
+        assert!(change.touches(&["mqtt".to_string(), "host".to_string()]));
+        assert!(change.touches(&["theme".to_string(), "mode".to_string()]));
+    }
+
+    // even a log that has them doesn't change them
+    let mut laptop = base.clone();
+    set_device_settings(&mut laptop, "laptop");
+    let change = ConfigChange::between(
+        &Table::try_from(&laptop).unwrap(),
+        &Table::try_from(&desktop).unwrap(),
+    );
+    let synced = change.apply(&laptop).unwrap();
+    assert!(!synced.sound);
+    assert_eq!(synced.mqtt.host, "broker");
+    assert_eq!(synced.theme.mode, ThemeMode::Light);
+    assert_eq!(
+        synced.hooks.on_work_start.as_deref(),
+        Some("notify-send laptop")
+    );
+    assert_eq!(synced.http_api.token.as_deref(), Some("laptop"));
+    assert_eq!(synced.mqtt.client_id, "laptop");
+    assert_eq!(synced.team_sync.address, "laptop:7777");
+    assert_eq!(synced.theme.font, Some(PathBuf::from("/laptop/font.ttf")));
+    assert_eq!(
+        synced.folder_sync.folder,
+        Some(PathBuf::from("/laptop/sync"))
+    );
+}
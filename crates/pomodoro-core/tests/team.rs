@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant};
+
+use pomodoro_core::{
+    team::{clock_offset, Message, PhaseState},
+    PomodoroPhase,
+};
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+#[test]
+fn messages_survive_being_sent() {
+    let state = PhaseState {
+        work: false,
+        length: Some(5 * MINUTE),
+        elapsed: Duration::from_millis(1234),
+        paused: false,
+    };
+    let messages = [
+        Message::Hello("ada".to_string()),
+        Message::Peers(vec!["ada".to_string(), "grace hopper".to_string()]),
+        Message::State { state, at: 42 },
+        Message::State {
+            state: PhaseState {
+                work: true,
+                length: None,
+                paused: true,
+                ..state
+            },
+            at: 43,
+        },
+        Message::Ping(7),
+        Message::Pong { sent: 7, host: 9 },
+    ];
+    for message in messages {
+        assert_eq!(Message::parse(&message.to_string()), Some(message));
+    }
+    assert_eq!(Message::parse("state\twork\tlots"), None);
+    assert_eq!(
+        Message::parse(&Message::Hello("a\tb".to_string()).to_string()),
+        Some(Message::Hello("a b".to_string()))
+    );
+}
+
+#[test]
+fn running_phases_make_up_for_the_delay() {
+    let now = Instant::now();
+    let state = PhaseState {
+        work: true,
+        length: Some(25 * MINUTE),
+        elapsed: 10 * MINUTE,
+        paused: false,
+    };
+    let phase = state.to_phase(now, Duration::from_secs(2));
+    assert!(matches!(phase, PomodoroPhase::Work { .. }));
+    assert_eq!(
+        phase.time_elapsed(now),
+        Some(10 * MINUTE + Duration::from_secs(2))
+    );
+
+    let paused = PhaseState {
+        paused: true,
+        ..state
+    }
+    .to_phase(now, Duration::from_secs(2));
+    assert!(paused.is_paused());
+    assert_eq!(paused.time_elapsed(now), Some(10 * MINUTE));
+    assert_eq!(
+        PhaseState::new(&paused, now),
+        PhaseState {
+            paused: true,
+            ..state
+        }
+    );
+}
+
+#[test]
+fn clock_offset_splits_the_round_trip() {
+    // the host is 500ms ahead, and it took 100ms each way
+    assert_eq!(clock_offset(1_000, 1_600, 1_200), 500);
+    assert_eq!(clock_offset(1_000, 700, 1_200), -400);
+}
@@ -0,0 +1,291 @@
+use std::time::Duration;
+
+use pomodoro_core::{
+    config::{Mode, Step, StepKind},
+    Config, EventKind, ManualClock, NamedTimer, PomodoroPhase, Stopwatch, Timer,
+};
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+fn config() -> Config {
+    Config {
+        work_time: 25 * MINUTE,
+        break_time: 5 * MINUTE,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn starts_on_a_paused_work_phase() {
+    let timer = Timer::with_clock(ManualClock::new(), &config());
+    assert!(matches!(timer.get_phase(), PomodoroPhase::Work { .. }));
+    assert!(timer.is_paused());
+    assert!(!timer.is_done());
+    assert_eq!(timer.get_time_left(), 25 * MINUTE);
+}
+
+#[test]
+fn paused_time_is_not_counted() {
+    let clock = ManualClock::new();
+    let mut timer = Timer::with_clock(clock.clone(), &config());
+    timer.start();
+    clock.advance(10 * MINUTE);
+    timer.pause();
+    clock.advance(30 * MINUTE);
+    assert!(!timer.is_done());
+    assert_eq!(timer.time_elapsed(), Some(10 * MINUTE));
+
+    timer.toggle();
+    clock.advance(5 * MINUTE);
+    assert_eq!(timer.time_elapsed(), Some(15 * MINUTE));
+    assert_eq!(timer.get_time_left(), 10 * MINUTE);
+}
+
+#[test]
+fn is_done_once_the_length_has_passed() {
+    let clock = ManualClock::new();
+    let mut timer = Timer::with_clock(clock.clone(), &config());
+    timer.start();
+    clock.advance(25 * MINUTE);
+    assert!(!timer.is_done());
+    clock.advance(Duration::from_millis(1));
+    assert!(timer.is_done());
+}
+
+#[test]
+fn cycles_between_work_and_break() {
+    let config = config();
+    let mut timer = Timer::with_clock(ManualClock::new(), &config);
+
+    let finished = timer.next_phase(&config);
+    assert!(matches!(finished, PomodoroPhase::Work { .. }));
+    assert!(matches!(timer.get_phase(), PomodoroPhase::Break { .. }));
+    assert_eq!(timer.get_phase().get_duration(), Some(5 * MINUTE));
+    // the next phase waits to be started
+    assert!(timer.is_paused());
+
+    let finished = timer.next_phase(&config);
+    assert!(matches!(finished, PomodoroPhase::Break { .. }));
+    assert!(matches!(timer.get_phase(), PomodoroPhase::Work { .. }));
+}
+
+#[test]
+fn next_phase_uses_the_current_config() {
+    let mut config = config();
+    let mut timer = Timer::with_clock(ManualClock::new(), &config);
+    config.break_time = 8 * MINUTE;
+    timer.next_phase(&config);
+    assert_eq!(timer.get_time_left(), 8 * MINUTE);
+}
+
+#[test]
+fn stats_count_full_cycles() {
+    let config = config();
+    let mut timer = Timer::with_clock(ManualClock::new(), &config);
+    assert_eq!(timer.get_stats().get_phase_count(), (1, 2));
+    assert_eq!(timer.get_stats().get_count(), 0);
+
+    timer.next_phase(&config);
+    assert_eq!(timer.get_stats().get_phase_count(), (2, 2));
+    assert_eq!(timer.get_stats().get_count(), 0);
+
+    timer.next_phase(&config);
+    assert_eq!(timer.get_stats().get_phase_count(), (1, 2));
+    assert_eq!(timer.get_stats().get_count(), 1);
+}
+
+#[test]
+fn percent_follows_the_clock() {
+    let clock = ManualClock::new();
+    let mut timer = Timer::with_clock(clock.clone(), &config());
+    timer.start();
+    clock.advance(5 * MINUTE);
+    assert_eq!(timer.to_percent().unwrap().map_to_value(100.0), 80.0);
+    clock.advance(Duration::from_millis(500));
+    assert_eq!(
+        timer.to_stepped_percent().unwrap().map_to_value(100.0),
+        80.0
+    );
+}
+
+#[test]
+fn records_events_for_the_hooks() {
+    let config = config();
+    let mut timer = Timer::with_clock(ManualClock::new(), &config);
+    timer.start();
+    timer.toggle();
+    timer.toggle();
+    // starting a running timer does nothing
+    timer.start();
+    timer.next_phase(&config);
+    timer.start();
+
+    let events = timer.take_events();
+    let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            EventKind::WorkStart,
+            EventKind::Pause,
+            EventKind::Resume,
+            EventKind::WorkEnd,
+            EventKind::BreakStart,
+        ]
+    );
+    assert_eq!(events[3].phase, "Work");
+    assert_eq!(events[3].duration, 25 * MINUTE);
+    assert_eq!(events[4].phase, "Break");
+    assert_eq!(events[4].cycle, 1);
+    assert!(timer.take_events().is_empty());
+}
+
+#[test]
+fn switching_profile_changes_the_times() {
+    let mut config = config();
+    assert!(config.set_profile("deep"));
+    assert_eq!(config.work_time, 50 * MINUTE);
+    assert_eq!(config.break_time, 10 * MINUTE);
+    assert_eq!(config.profile.as_deref(), Some("deep"));
+    assert!(!config.set_profile("missing"));
+}
+
+#[test]
+fn named_timers_run_on_their_own() {
+    let clock = ManualClock::new();
+    let mut tea = NamedTimer::with_clock(clock.clone(), "Tea", 4 * MINUTE);
+    tea.toggle();
+    clock.advance(3 * MINUTE);
+    assert_eq!(tea.get_time_left(), MINUTE);
+    clock.advance(2 * MINUTE);
+    assert!(tea.is_done());
+
+    tea.reset();
+    assert!(tea.is_paused());
+    assert!(!tea.is_done());
+    assert_eq!(tea.get_time_left(), 4 * MINUTE);
+}
+
+#[test]
+fn stopwatch_fills_once_per_lap() {
+    let clock = ManualClock::new();
+    let mut stopwatch = Stopwatch::with_clock(clock.clone(), MINUTE);
+    stopwatch.toggle();
+    clock.advance(Duration::from_secs(90));
+    assert_eq!(stopwatch.to_percent().map_to_value(100.0), 50.0);
+
+    stopwatch.lap();
+    clock.advance(Duration::from_secs(30));
+    stopwatch.lap();
+    assert_eq!(
+        stopwatch.get_laps(),
+        [Duration::from_secs(90), Duration::from_secs(30)]
+    );
+    assert_eq!(stopwatch.time_elapsed(), 2 * MINUTE);
+
+    stopwatch.reset(MINUTE);
+    assert!(stopwatch.is_paused());
+    assert!(stopwatch.get_laps().is_empty());
+}
+
+#[test]
+fn flowtime_work_goes_on_until_it_is_ended() {
+    let config = Config {
+        mode: Mode::Flowtime,
+        ..config()
+    };
+    let clock = ManualClock::new();
+    let mut timer = Timer::with_clock(clock.clone(), &config);
+    assert!(timer.get_phase().is_open_ended());
+    timer.start();
+    clock.advance(3 * 60 * MINUTE);
+    assert!(!timer.is_done());
+    assert_eq!(timer.time_elapsed(), Some(3 * 60 * MINUTE));
+
+    // past the last tier
+    timer.next_phase(&config);
+    assert_eq!(timer.get_time_left(), 10 * MINUTE);
+    timer.next_phase(&config);
+    assert!(timer.get_phase().is_open_ended());
+}
+
+fn step(kind: StepKind, name: Option<&str>, minutes: u32) -> Step {
+    Step {
+        kind,
+        name: name.map(str::to_string),
+        length: minutes * MINUTE,
+        color: None,
+        sound: None,
+    }
+}
+
+#[test]
+fn sequences_loop_and_can_be_jumped_around() {
+    let config = Config {
+        sequence: vec![
+            step(StepKind::Work, None, 50),
+            step(StepKind::Break, None, 10),
+            step(StepKind::Work, None, 50),
+            step(StepKind::Break, Some("Long break"), 30),
+        ],
+        ..config()
+    };
+    let mut timer = Timer::with_clock(ManualClock::new(), &config);
+    assert_eq!(timer.get_step().map(|(i, _)| i), Some(0));
+    assert_eq!(timer.get_time_left(), 50 * MINUTE);
+
+    for _ in 0..3 {
+        timer.next_phase(&config);
+    }
+    let (i, step) = timer.get_step().unwrap();
+    assert_eq!((i, step.get_name()), (3, "Long break"));
+    assert!(matches!(timer.get_phase(), PomodoroPhase::Break { .. }));
+    assert_eq!(timer.get_time_left(), 30 * MINUTE);
+
+    timer.next_phase(&config);
+    assert_eq!(timer.get_step().map(|(i, _)| i), Some(0));
+
+    assert!(timer.jump_to(1, &config));
+    assert!(timer.is_paused());
+    assert_eq!(timer.get_time_left(), 10 * MINUTE);
+    assert!(!timer.jump_to(4, &config));
+    assert_eq!(timer.get_step().map(|(i, _)| i), Some(1));
+}
+
+#[test]
+fn sequences_are_only_used_in_pomodoro_mode() {
+    let config = Config {
+        mode: Mode::Flowtime,
+        sequence: vec![step(StepKind::Break, None, 10)],
+        ..config()
+    };
+    let mut timer = Timer::with_clock(ManualClock::new(), &config);
+    assert!(timer.get_step().is_none());
+    assert!(timer.get_phase().is_open_ended());
+    assert!(!timer.jump_to(0, &config));
+}
+
+#[test]
+fn only_phases_not_started_get_shortened() {
+    let clock = ManualClock::new();
+    let mut timer = Timer::with_clock(clock.clone(), &config());
+    assert!(!timer.shorten_to(30 * MINUTE));
+    assert!(timer.shorten_to(20 * MINUTE));
+    assert_eq!(timer.get_time_left(), 20 * MINUTE);
+
+    timer.start();
+    assert!(!timer.shorten_to(10 * MINUTE));
+    assert_eq!(timer.get_time_left(), 20 * MINUTE);
+}
+
+#[test]
+fn shortened_steps_keep_their_name() {
+    let config = Config {
+        sequence: vec![step(StepKind::Work, Some("Deep work"), 50)],
+        ..config()
+    };
+    let mut timer = Timer::with_clock(ManualClock::new(), &config);
+    assert!(timer.shorten_to(20 * MINUTE));
+    assert_eq!(timer.get_time_left(), 20 * MINUTE);
+    let (i, step) = timer.get_step().unwrap();
+    assert_eq!((i, step.get_name()), (0, "Deep work"));
+}
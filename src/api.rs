@@ -0,0 +1,203 @@
+use std::{
+    io::{BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui;
+use pomodoro_core::{
+    api::{json_string, ConfigUpdate, Request, Status},
+    config::HttpApi,
+};
+
+use crate::ui::Action;
+
+/// How often the event stream looks for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a connection can take to send its request before it's dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the connections need, shared between their threads
+struct Shared {
+    ctx: egui::Context,
+    actions: Mutex<Sender<Action>>,
+    status: Mutex<Option<Status>>,
+    token: Option<String>,
+}
+
+impl Shared {
+    fn send(&self, action: Action) {
+        let _ = self.actions.lock().unwrap().send(action);
+        // wakes the app up so the action gets handled even if the window is hidden
+        self.ctx.request_repaint();
+    }
+
+    fn get_status(&self) -> Option<Status> {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+/// The HTTP server, which turns requests into the same actions as the buttons
+pub struct Api {
+    shared: Arc<Shared>,
+    actions: Receiver<Action>,
+}
+
+impl Api {
+    pub fn new(ctx: &egui::Context, config: &HttpApi) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let listener = match TcpListener::bind(&config.address) {
+            Ok(listener) => listener,
+            Err(e) => {
+                println!("Could not start the API on {}: {e}", config.address);
+                return None;
+            }
+        };
+        let (sender, actions) = mpsc::channel();
+        let shared = Arc::new(Shared {
+            ctx: ctx.clone(),
+            actions: Mutex::new(sender),
+            status: Mutex::new(None),
+            token: config.token.clone(),
+        });
+        let server = shared.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = server.clone();
+                thread::spawn(move || handle(stream, &shared));
+            }
+        });
+        Some(Self { shared, actions })
+    }
+
+    /// Gets called every frame with what the timer is doing
+    pub fn update(&self, status: Status) {
+        *self.shared.status.lock().unwrap() = Some(status);
+    }
+
+    pub fn get_actions(&self) -> Vec<Action> {
+        self.actions.try_iter().collect()
+    }
+}
+
+fn handle(stream: TcpStream, shared: &Shared) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    if stream.set_read_timeout(Some(READ_TIMEOUT)).is_err() {
+        return;
+    }
+    let Some(request) = Request::read(&mut BufReader::new(stream)) else {
+        respond(&mut writer, "400 Bad Request", &error("Not a HTTP request"));
+        return;
+    };
+    if !request.is_authorized(shared.token.as_deref()) {
+        respond(
+            &mut writer,
+            "401 Unauthorized",
+            &error("Wrong or missing token"),
+        );
+        return;
+    }
+    let action = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => {
+            match shared.get_status() {
+                Some(status) => respond(&mut writer, "200 OK", &status.to_json()),
+                None => respond(
+                    &mut writer,
+                    "503 Service Unavailable",
+                    &error("Not ready yet"),
+                ),
+            }
+            return;
+        }
+        ("GET", "/events") => {
+            stream_events(&mut writer, shared);
+            return;
+        }
+        ("POST", "/toggle") => Action::TogglePhase,
+        ("POST", "/skip") => Action::NextPhase,
+        ("POST", "/reset") => Action::Reset,
+        ("PUT", "/config") => match ConfigUpdate::parse(&request.body) {
+            Ok(update) => {
+                if let Some(work_time) = update.work_time {
+                    shared.send(Action::ModifyWorkPhaseConfig(work_time));
+                }
+                if let Some(break_time) = update.break_time {
+                    shared.send(Action::ModifyBreakPhaseConfig(break_time));
+                }
+                respond(&mut writer, "200 OK", "{\"ok\":true}");
+                return;
+            }
+            Err(e) => {
+                respond(&mut writer, "400 Bad Request", &error(&e));
+                return;
+            }
+        },
+        (_, "/status" | "/events" | "/toggle" | "/skip" | "/reset" | "/config") => {
+            respond(
+                &mut writer,
+                "405 Method Not Allowed",
+                &error("Wrong method"),
+            );
+            return;
+        }
+        _ => {
+            respond(&mut writer, "404 Not Found", &error("No such endpoint"));
+            return;
+        }
+    };
+    shared.send(action);
+    respond(&mut writer, "200 OK", "{\"ok\":true}");
+}
+
+fn error(message: &str) -> String {
+    format!("{{\"error\":{}}}", json_string(message))
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+}
+
+/// Sends a `phase` event whenever the timer moves on, starts or stops, and a `tick` every
+/// second, until the client goes away
+fn stream_events(stream: &mut TcpStream, shared: &Shared) {
+    let headers = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n";
+    if stream.write_all(headers.as_bytes()).is_err() {
+        return;
+    }
+    let mut last: Option<Status> = None;
+    let mut last_tick = Instant::now();
+    loop {
+        if let Some(status) = shared.get_status() {
+            let event = if last.as_ref().is_none_or(|l| status.differs_from(l)) {
+                Some("phase")
+            } else if last_tick.elapsed() >= TICK_INTERVAL {
+                Some("tick")
+            } else {
+                None
+            };
+            if let Some(event) = event {
+                let message = format!("event: {event}\ndata: {}\n\n", status.to_json());
+                if stream.write_all(message.as_bytes()).is_err() {
+                    return;
+                }
+                last_tick = Instant::now();
+                last = Some(status);
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use eframe::egui::{self, RichText};
+
+use pomodoro_core::{format_time, Timer};
+
+use crate::{ui::Action, viewport};
+
+/// How long each suggestion is shown for before moving on to the next one
+const SUGGESTION_TIME: Duration = Duration::from_secs(30);
+
+/// Picks a different suggestion every so often, starting each break on a different one
+pub fn get_suggestion<'a>(suggestions: &'a [String], timer: &Timer) -> Option<&'a str> {
+    if suggestions.is_empty() {
+        return None;
+    }
+    let shown = timer.time_elapsed().unwrap_or_default().as_secs() / SUGGESTION_TIME.as_secs();
+    let i = (timer.get_stats().get_count() + shown as usize) % suggestions.len();
+    Some(&suggestions[i])
+}
+
+pub fn draw_break_screen(
+    ctx: &egui::Context,
+    timer: &Timer,
+    suggestion: Option<&str>,
+    postpones_left: u32,
+) -> Action {
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("break_screen"),
+        viewport::break_viewport(),
+        |ctx, _class| {
+            // it can only be left by finishing or postponing the break
+            if ctx.input(|i| i.viewport().close_requested()) {
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            }
+            let mut action = Action::None;
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(ui.available_height() / 3.0);
+                    ui.heading(RichText::new("Break").size(40.0));
+                    ui.label(RichText::new(format_time(timer.get_time_left())).size(80.0));
+                    if let Some(suggestion) = suggestion {
+                        ui.label(RichText::new(suggestion).size(25.0));
+                    }
+                    ui.add_space(20.0);
+                    if timer.is_paused() && ui.button("Start break").clicked() {
+                        action = Action::TogglePhase;
+                    }
+                    let postpone = ui.add_enabled(
+                        postpones_left > 0,
+                        egui::Button::new(format!("Postpone 5 min ({postpones_left} left)")),
+                    );
+                    if postpone.clicked() {
+                        action = Action::PostponeBreak;
+                    }
+                });
+            });
+            action
+        },
+    )
+}
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use eframe::{
-    egui::{self, Response, Sense, Widget},
+    egui::{self, Response, Sense, Widget, WidgetInfo, WidgetType},
     emath::Align2,
     epaint::{
         tessellator::{Path, PathType},
@@ -9,16 +9,149 @@ use eframe::{
     },
 };
 
-use crate::{precomputed::CIRCLE, timer::PomodoroPhase, ui::Action, utils::Percent};
+use pomodoro_core::{
+    config::Theme, format_time, NamedTimer, Percent, PomodoroPhase, Stopwatch, Timer,
+};
+
+use crate::{precomputed::CIRCLE, theme::ThemeColors, ui::Action};
+
+/// The radius of the ring in the default window, which the sizes in the theme are for
+const BASE_RADIUS: f32 = 95.0;
+
+/// What the ring shows, so it can be used for more than the pomodoro timer
+pub trait RingTimer {
+    fn get_name(&self) -> &str;
+    /// The time in the middle of the ring, which is how long is left unless it counts up
+    fn get_time(&self) -> Duration;
+    fn counts_up(&self) -> bool {
+        false
+    }
+    fn is_paused(&self) -> bool;
+    fn toggle(&mut self);
+    /// The color while it's running
+    fn get_color(&self, theme: &Theme) -> Color32;
+}
+
+impl RingTimer for Timer {
+    fn get_name(&self) -> &str {
+        match self.get_step() {
+            Some((_, step)) => step.get_name(),
+            None => self.get_phase().get_name(),
+        }
+    }
+    fn get_time(&self) -> Duration {
+        if self.get_phase().is_open_ended() {
+            self.time_elapsed().unwrap_or_default()
+        } else {
+            self.get_time_left()
+        }
+    }
+    fn counts_up(&self) -> bool {
+        self.get_phase().is_open_ended()
+    }
+    fn is_paused(&self) -> bool {
+        self.is_paused()
+    }
+    fn toggle(&mut self) {
+        self.toggle()
+    }
+    fn get_color(&self, theme: &Theme) -> Color32 {
+        let step_color = self
+            .get_step()
+            .and_then(|(_, step)| step.color.as_ref())
+            .and_then(|c| Color32::from_hex(c).ok());
+        if let Some(color) = step_color {
+            return color;
+        }
+        match self.get_phase() {
+            PomodoroPhase::Work { .. } => theme.get_work_color(),
+            PomodoroPhase::Break { .. } => theme.get_break_color(),
+        }
+    }
+}
+
+impl RingTimer for NamedTimer {
+    fn get_name(&self) -> &str {
+        self.get_name()
+    }
+    fn get_time(&self) -> Duration {
+        self.get_time_left()
+    }
+    fn is_paused(&self) -> bool {
+        self.is_paused()
+    }
+    fn toggle(&mut self) {
+        self.toggle()
+    }
+    fn get_color(&self, theme: &Theme) -> Color32 {
+        theme.get_work_color()
+    }
+}
+
+impl RingTimer for Stopwatch {
+    fn get_name(&self) -> &str {
+        "Stopwatch"
+    }
+    fn get_time(&self) -> Duration {
+        self.time_elapsed()
+    }
+    fn counts_up(&self) -> bool {
+        true
+    }
+    fn is_paused(&self) -> bool {
+        self.is_paused()
+    }
+    fn toggle(&mut self) {
+        self.toggle()
+    }
+    fn get_color(&self, theme: &Theme) -> Color32 {
+        theme.get_work_color()
+    }
+}
 
 pub struct ProgressCircle<'a> {
     amount: Percent,
-    phase: &'a mut PomodoroPhase,
+    timer: &'a mut dyn RingTimer,
+    theme: &'a Theme,
+    compact: bool,
+    fill: bool,
 }
 
 impl<'a> ProgressCircle<'a> {
-    pub fn new(p: Percent, phase: &'a mut PomodoroPhase) -> Self {
-        Self { amount: p, phase }
+    pub fn new(p: Percent, timer: &'a mut dyn RingTimer, theme: &'a Theme) -> Self {
+        Self {
+            amount: p,
+            timer,
+            theme,
+            compact: false,
+            fill: false,
+        }
+    }
+
+    /// Fills all the available space with the ring and only shows the time left in it
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Takes up all the available space, instead of leaving room below for other widgets
+    pub fn fill(mut self, fill: bool) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    fn get_color(&self, ui: &egui::Ui) -> Color32 {
+        let color = self.timer.get_color(self.theme);
+        if self.timer.is_paused() {
+            ui.visuals().gray_out(color)
+        } else {
+            color
+        }
+    }
+
+    /// How much bigger or smaller everything is than in the default window
+    fn get_scale(radius: f32) -> f32 {
+        radius / BASE_RADIUS
     }
 
     fn get_points(&self, center: Pos2, radius: f32) -> Vec<Pos2> {
@@ -38,7 +171,7 @@ impl<'a> ProgressCircle<'a> {
             let center = outer.center();
             ((center.x - hover_pos.x).abs().powf(2.0) + (center.y - hover_pos.y).abs().powf(2.0))
                 .sqrt()
-                <= radius + 7.5
+                <= radius + self.theme.ring_thickness * Self::get_scale(radius)
         } else {
             false
         }
@@ -46,7 +179,7 @@ impl<'a> ProgressCircle<'a> {
 
     fn sense_hover(&self, ui: &mut egui::Ui, response: &Response, radius: f32, outer: Rect) {
         if response.hovered() && self.is_in_circle(response, radius, outer) {
-            let mut color = self.phase.to_color(ui);
+            let mut color = self.get_color(ui);
             color = color.gamma_multiply(0.5);
             ui.painter()
                 .circle(outer.center(), radius, color, Stroke::NONE);
@@ -71,7 +204,10 @@ impl<'a> ProgressCircle<'a> {
             path.stroke(
                 1.0,
                 PathType::Open,
-                Stroke::new(7.5, self.phase.to_color(ui)),
+                Stroke::new(
+                    self.theme.ring_thickness * Self::get_scale(radius),
+                    self.get_color(ui),
+                ),
                 &mut mesh,
             );
             // paints it
@@ -79,72 +215,94 @@ impl<'a> ProgressCircle<'a> {
         }
     }
 
-    fn paint_info(&self, ui: &mut egui::Ui, outer: Rect) {
-        let seconds_left = self.phase.get_duration().as_secs()
-            - self
-                .phase
-                .time_elapsed()
-                .unwrap_or(Duration::new(0, 0))
-                .as_secs();
-        let mut minutes = (seconds_left / 60).to_string();
-        let mut seconds = (seconds_left % 60).to_string();
-        if minutes.len() < 2 {
-            minutes = "0".to_owned() + &minutes;
-        }
-        if seconds.len() < 2 {
-            seconds = "0".to_owned() + &seconds;
-        }
-        let time_left = minutes + ":" + &seconds;
+    fn paint_info(&self, ui: &mut egui::Ui, radius: f32, outer: Rect) {
+        let time_left = format_time(self.timer.get_time());
+        let scale = Self::get_scale(radius);
+        let time_font_size = self.theme.time_font_size * scale;
 
         // adds the text in the middle
         ui.painter().text(
             outer.center(),
             Align2::CENTER_CENTER,
             time_left,
-            FontId::new(50.0, FontFamily::default()),
-            Color32::WHITE,
+            FontId::new(time_font_size, FontFamily::default()),
+            ui.visuals().text_color(),
         );
-        let phase_text_placement = Pos2::new(outer.center().x, outer.center().y + 50.0);
-        let phase_text = match self.phase {
-            PomodoroPhase::Work { .. } => "Work",
-            PomodoroPhase::Break { .. } => "Break",
-        };
+        if self.compact {
+            return;
+        }
+        let phase_text_placement = Pos2::new(outer.center().x, outer.center().y + time_font_size);
         ui.painter().text(
             phase_text_placement,
             Align2::CENTER_CENTER,
-            phase_text,
-            FontId::new(25.0, FontFamily::default()),
-            Color32::WHITE,
+            self.timer.get_name(),
+            FontId::new(self.theme.phase_font_size * scale, FontFamily::default()),
+            ui.visuals().text_color(),
         );
     }
 }
 
 impl<'a> Widget for ProgressCircle<'a> {
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
-        let radius = ui.available_width() / 3.5;
+        let (radius, size, sense) = if self.compact {
+            // the ring goes right up to the edge of the window, and the window gets dragged by it
+            let size = ui.available_size();
+            (
+                size.min_elem() / 2.0 - self.theme.ring_thickness,
+                size,
+                Sense::click_and_drag(),
+            )
+        } else {
+            let size = if self.fill {
+                ui.available_size()
+            } else {
+                Vec2::new(ui.available_width(), ui.available_height() / 1.5)
+            };
+            // keeps the proportions of the original fixed size window
+            ((size.x / 3.5).min(size.y / 2.8), size, Sense::click())
+        };
         // sets the amount of space the widget takes up
-        let (outer, response) = ui.allocate_exact_size(
-            Vec2::new(ui.available_width(), ui.available_height() / 1.5),
-            Sense::click(),
-        );
+        let (outer, response) = ui.allocate_exact_size(size, sense);
         // hover effect
         self.sense_hover(ui, &response, radius, outer);
 
         // click effect
         if let Action::TogglePhase = self.sense_click(&response, radius, outer) {
-            self.phase.toggle();
+            self.timer.toggle();
         }
 
         // paints the thin circle behind the progress one
         ui.painter().circle_stroke(
             outer.center(),
             radius,
-            Stroke::new(1.5, ui.style().visuals.weak_text_color()),
+            Stroke::new(
+                1.5 * Self::get_scale(radius),
+                ui.style().visuals.weak_text_color(),
+            ),
         );
 
         self.paint_progress_circle(ui, radius, outer);
 
-        self.paint_info(ui, outer);
+        self.paint_info(ui, radius, outer);
+
+        // what screen readers see, as the ring and text are just painted
+        let mut label = format!(
+            "{}, {} {}",
+            self.timer.get_name(),
+            format_time(self.timer.get_time()),
+            if self.timer.counts_up() {
+                "so far"
+            } else {
+                "left"
+            }
+        );
+        if self.timer.is_paused() {
+            label += ", paused";
+        }
+        response.widget_info(|| WidgetInfo {
+            value: Some(self.amount.map_to_value(100.0)),
+            ..WidgetInfo::labeled(WidgetType::ProgressIndicator, &label)
+        });
 
         response
     }
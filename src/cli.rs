@@ -0,0 +1,81 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use pomodoro_core::{
+    args::{Subcommand, USAGE},
+    history::Summary,
+    Config, History,
+};
+
+use crate::{folder_sync::FolderSync, history, instance, paths, utils};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Does what was asked for on the command line instead of opening the window, giving back the
+/// exit code. `config_file` is from `--config`, for finding the sync folder and the instance
+pub fn run(subcommand: Subcommand, config_file: Option<PathBuf>) -> i32 {
+    match subcommand {
+        Subcommand::Status => match paths::get_config_file(config_file)
+            .ok()
+            .and_then(|file| instance::query_status(&file))
+        {
+            Some(status) => println!("{status}"),
+            None => {
+                println!("Not running");
+                return 1;
+            }
+        },
+        Subcommand::Stats => {
+            let history = load_history(config_file);
+            let today = utils::start_of_today();
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            println!(
+                "{:<12} {:>9} {:>8} {:>8}",
+                "", "Completed", "Skipped", "Worked"
+            );
+            for (name, since) in [
+                ("Today", today),
+                ("Last 7 days", now.saturating_sub(7 * DAY)),
+                ("All time", 0),
+            ] {
+                let Summary {
+                    completed,
+                    skipped,
+                    worked,
+                } = history.summarize(since);
+                println!(
+                    "{name:<12} {completed:>9} {skipped:>8} {:>8}",
+                    format_hours(worked)
+                );
+            }
+        }
+        Subcommand::Export => print!("{}", load_history(config_file).to_csv()),
+        Subcommand::Help => print!("{USAGE}"),
+    }
+    0
+}
+
+/// Everyone's history if there's a sync folder, otherwise just what's been done here. The config
+/// is read straight from the file so nothing else gets printed
+fn load_history(config_file: Option<PathBuf>) -> History {
+    let config: Option<Config> = paths::get_config_file(config_file)
+        .ok()
+        .and_then(|file| std::fs::read_to_string(file).ok())
+        .and_then(|data| toml::from_str(&data).ok());
+    config
+        .and_then(|config| FolderSync::new(&config.folder_sync))
+        .map_or_else(history::load, |mut sync| {
+            sync.wait();
+            sync.load_history()
+        })
+}
+
+/// Like `12h 05m`
+fn format_hours(time: Duration) -> String {
+    let minutes = time.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
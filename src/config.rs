@@ -1,124 +1,294 @@
-use directories::BaseDirs;
-use serde::{Deserialize, Serialize};
+use crate::paths;
+use pomodoro_core::{
+    args::{Applied, Overrides},
+    config::{BreakScreen, Config, EyeReminders, Mode, Step, Theme, ThemeMode, TimerConfig},
+    schedule::Schedule,
+    sync::ConfigChange,
+};
 use std::{
-    path::PathBuf,
-    sync::{Arc, Mutex},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
     time::Duration,
 };
 
-#[derive(Debug, Copy, Clone)]
+/// How long to wait for more changes before saving
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
 pub enum Status {
     Saving,
     Saved,
     Loading,
     Loaded,
+    /// The last save failed
+    Error(String),
     None,
 }
 
-fn load(status: Arc<Mutex<Status>>, config: Arc<Mutex<Config>>, mut config_file: PathBuf) {
+/// Leaves the config as it was if the file can't be used
+fn load(
+    status: Arc<Mutex<Status>>,
+    config: Arc<Mutex<Config>>,
+    config_file: PathBuf,
+) -> Result<(), String> {
     println!("Loading");
     *status.lock().unwrap() = Status::Loading;
-    config_file.push("Pomodoro/config.toml");
     if config_file.exists() {
-        if let Ok(data) = std::fs::read_to_string(config_file) {
-            *config.lock().unwrap() = toml::from_str(&data).unwrap();
+        if let Ok(data) = std::fs::read_to_string(&config_file) {
+            let loaded: Config = toml::from_str(&data)
+                .map_err(|e| e.to_string())
+                .and_then(|loaded: Config| loaded.validate().map(|_| loaded))
+                .map_err(|e| format!("Could not load {}: {e}", config_file.display()))?;
+            *config.lock().unwrap() = loaded;
         }
     }
     *status.lock().unwrap() = Status::Loaded;
     println!("Loaded");
+    Ok(())
 }
 
-fn save(status: Arc<Mutex<Status>>, config: Arc<Mutex<Config>>, mut storage_dir: PathBuf) {
-    println!("Saving");
-    while !matches!(
-        *status.lock().unwrap(),
-        Status::Saved | Status::None | Status::Loaded
-    ) {
-        std::thread::sleep(Duration::from_nanos(500));
+/// Writes to a temporary file next to it first, so it's never left half written
+fn write(config_file: &Path, config: &Config) -> Result<(), String> {
+    let data = toml::to_string(config).map_err(|e| e.to_string())?;
+    if let Some(dir) = config_file.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
     }
+    let temp_file = config_file.with_extension("toml.tmp");
+    std::fs::File::create(&temp_file)
+        .and_then(|mut file| {
+            file.write_all(data.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_file, config_file))
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&temp_file);
+            e.to_string()
+        })
+}
+
+fn save(status: &Mutex<Status>, config: &Config, config_file: &Path) {
+    println!("Saving");
     *status.lock().unwrap() = Status::Saving;
-    storage_dir.push("Pomodoro");
-    if !storage_dir.exists() {
-        let _ = std::fs::create_dir_all(&storage_dir);
-    }
-    storage_dir.push("config.toml");
-    let _ = std::fs::write(
-        storage_dir,
-        toml::to_string(&*config.lock().unwrap()).unwrap(),
-    );
-    *status.lock().unwrap() = Status::Saved;
-    println!("Saved");
+    match write(config_file, config) {
+        Ok(()) => {
+            *status.lock().unwrap() = Status::Saved;
+            println!("Saved");
+        }
+        Err(e) => {
+            let e = format!("Could not save {}: {e}", config_file.display());
+            println!("{e}");
+            *status.lock().unwrap() = Status::Error(e);
+        }
+    }
+}
+
+enum SaveRequest {
+    /// Written once there haven't been any more for a bit
+    Later(Config),
+    /// Written straight away, answering once it's done
+    Now(Config, Sender<()>),
+}
+
+/// The only thing that writes the config, so saves never overlap and a slider being dragged
+/// only gets written once it stops
+fn run_writer(status: Arc<Mutex<Status>>, config_file: PathBuf, requests: Receiver<SaveRequest>) {
+    let mut pending = None;
+    loop {
+        let request = if pending.is_some() {
+            requests.recv_timeout(SAVE_DELAY)
+        } else {
+            requests.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        };
+        match request {
+            Ok(SaveRequest::Later(config)) => pending = Some(config),
+            Ok(SaveRequest::Now(config, done)) => {
+                pending = None;
+                save(&status, &config, &config_file);
+                let _ = done.send(());
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if let Some(config) = pending.take() {
+                    save(&status, &config, &config_file);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                if let Some(config) = pending.take() {
+                    save(&status, &config, &config_file);
+                }
+                return;
+            }
+        }
+    }
 }
 
 pub struct ConfigManager {
     pub status: Arc<Mutex<Status>>,
     pub config: Arc<Mutex<Config>>,
+    file: PathBuf,
+    overrides: Option<Applied>,
+    saver: Sender<SaveRequest>,
 }
 
 impl ConfigManager {
-    pub fn new() -> Self {
-        Self {
-            status: Arc::new(Mutex::new(Status::None)),
+    /// Uses `file` instead of the usual config file if it's given, failing if there's nowhere
+    /// to keep it
+    pub fn new(file: Option<PathBuf>) -> Result<Self, String> {
+        let file = paths::get_config_file(file)?;
+        let status = Arc::new(Mutex::new(Status::None));
+        let (saver, requests) = mpsc::channel();
+        let (writer_status, writer_file) = (status.clone(), file.clone());
+        std::thread::spawn(move || run_writer(writer_status, writer_file, requests));
+        Ok(Self {
+            status,
             config: Arc::new(Mutex::new(Config::default())),
-        }
+            file,
+            overrides: None,
+            saver,
+        })
     }
 
-    fn get_save_dir() -> Option<PathBuf> {
-        BaseDirs::new().map(|d| d.config_dir().to_path_buf())
+    pub fn load_blocking(&mut self) -> Result<(), String> {
+        load(self.status.clone(), self.config.clone(), self.file.clone())
     }
 
-    pub fn load(&mut self) {
-        let (status, config, config_file) = (
-            self.status.clone(),
-            self.config.clone(),
-            Self::get_save_dir().unwrap(),
-        );
-        std::thread::spawn(move || {
-            load(status, config, config_file);
-        });
+    /// What gets written, without what was given on the command line
+    pub fn get_saved_config(&self) -> Config {
+        let mut config = self.config.lock().unwrap().clone();
+        if let Some(overrides) = &self.overrides {
+            overrides.revert(&mut config);
+        }
+        config
     }
 
-    pub fn load_blocking(&mut self) {
-        let config_file = Self::get_save_dir().unwrap();
-        load(self.status.clone(), self.config.clone(), config_file);
+    pub fn save_blocking(&mut self) {
+        let (done, finished) = mpsc::channel();
+        let request = SaveRequest::Now(self.get_saved_config(), done);
+        if self.saver.send(request).is_ok() {
+            let _ = finished.recv();
+        }
     }
 
     pub fn save(&mut self) {
-        let (status, config, storage_dir) = (
-            self.status.clone(),
-            self.config.clone(),
-            Self::get_save_dir().unwrap(),
-        );
-        std::thread::spawn(move || {
-            save(status, config, storage_dir);
-        });
+        let _ = self.saver.send(SaveRequest::Later(self.get_saved_config()));
     }
 
-    pub fn get_break_time(&self) -> Duration {
-        self.config.lock().unwrap().break_time
+    /// Uses the settings from the command line, only writing them to the file if `save` is set
+    pub fn apply_overrides(&mut self, overrides: &Overrides, save: bool) -> Result<(), String> {
+        let applied = overrides.apply(&mut self.config.lock().unwrap())?;
+        if save {
+            self.save_blocking();
+        } else {
+            self.overrides = Some(applied);
+        }
+        Ok(())
     }
-    pub fn get_work_time(&self) -> Duration {
-        self.config.lock().unwrap().work_time
+
+    /// Takes what other devices changed in the sync folder, and saves it
+    pub fn apply_synced(&mut self, change: &ConfigChange) -> Result<(), String> {
+        let synced = change.apply(&self.config.lock().unwrap())?;
+        synced.validate()?;
+        *self.config.lock().unwrap() = synced;
+        self.save();
+        Ok(())
     }
+
     pub fn set_work_time(&self, time: Duration) {
-        self.config.lock().unwrap().work_time = time;
+        let mut config = self.config.lock().unwrap();
+        config.work_time = time;
+        config.profile = None;
     }
     pub fn set_break_time(&self, time: Duration) {
-        self.config.lock().unwrap().break_time = time;
+        let mut config = self.config.lock().unwrap();
+        config.break_time = time;
+        config.profile = None;
     }
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct Config {
-    pub work_time: Duration,
-    pub break_time: Duration,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            work_time: Duration::from_secs(30 * 60),
-            break_time: Duration::from_secs(15 * 60),
+    pub fn get_mode(&self) -> Mode {
+        self.config.lock().unwrap().mode
+    }
+    pub fn set_mode(&self, mode: Mode) {
+        self.config.lock().unwrap().mode = mode;
+    }
+    pub fn set_flowtime_ratio(&self, ratio: f32) {
+        self.config.lock().unwrap().flowtime.ratio = ratio;
+    }
+    pub fn get_countdown_time(&self) -> Duration {
+        self.config.lock().unwrap().countdown_time
+    }
+    pub fn set_countdown_time(&self, time: Duration) {
+        self.config.lock().unwrap().countdown_time = time;
+    }
+    pub fn get_lap_length(&self) -> Duration {
+        self.config.lock().unwrap().lap_length
+    }
+    pub fn set_lap_length(&self, time: Duration) {
+        self.config.lock().unwrap().lap_length = time;
+    }
+    pub fn get_profile(&self) -> Option<String> {
+        self.config.lock().unwrap().profile.clone()
+    }
+    pub fn get_profile_names(&self) -> Vec<String> {
+        let config = self.config.lock().unwrap();
+        config.profiles.iter().map(|p| p.name.clone()).collect()
+    }
+    /// Switches to the profile with the given name, setting the work and break time to it's own
+    pub fn set_profile(&self, name: &str) -> bool {
+        self.config.lock().unwrap().set_profile(name)
+    }
+    pub fn get_sound(&self) -> bool {
+        self.config.lock().unwrap().sound
+    }
+    pub fn get_theme(&self) -> Theme {
+        self.config.lock().unwrap().theme.clone()
+    }
+    pub fn set_theme_mode(&self, mode: ThemeMode) {
+        self.config.lock().unwrap().theme.mode = mode;
+    }
+    pub fn set_reduce_motion(&self, reduce_motion: bool) {
+        self.config.lock().unwrap().theme.reduce_motion = reduce_motion;
+    }
+    pub fn get_window_size(&self) -> Option<[f32; 2]> {
+        self.config.lock().unwrap().window_size
+    }
+    pub fn get_window_position(&self) -> Option<[f32; 2]> {
+        self.config.lock().unwrap().window_position
+    }
+    pub fn set_window_geometry(&self, size: [f32; 2], position: [f32; 2]) {
+        let mut config = self.config.lock().unwrap();
+        config.window_size = Some(size);
+        config.window_position = Some(position);
+    }
+    pub fn get_break_screen(&self) -> BreakScreen {
+        self.config.lock().unwrap().break_screen.clone()
+    }
+    pub fn get_eye_reminders(&self) -> EyeReminders {
+        self.config.lock().unwrap().eye_reminders.clone()
+    }
+    pub fn get_schedule(&self) -> Schedule {
+        self.config.lock().unwrap().schedule.clone()
+    }
+    pub fn get_sequence(&self) -> Vec<Step> {
+        self.config.lock().unwrap().sequence.clone()
+    }
+    pub fn get_timers(&self) -> Vec<TimerConfig> {
+        self.config.lock().unwrap().timers.clone()
+    }
+    pub fn add_timer(&self, timer: TimerConfig) {
+        self.config.lock().unwrap().timers.push(timer);
+    }
+    pub fn remove_timer(&self, i: usize) {
+        let mut config = self.config.lock().unwrap();
+        if i < config.timers.len() {
+            config.timers.remove(i);
         }
     }
+    pub fn get_compact_position(&self) -> Option<[f32; 2]> {
+        self.config.lock().unwrap().compact_position
+    }
+    pub fn set_compact_position(&self, position: [f32; 2]) {
+        self.config.lock().unwrap().compact_position = Some(position);
+    }
 }
@@ -0,0 +1,149 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use eframe::egui;
+use pomodoro_core::api::Status;
+use zbus::{blocking::Connection, interface, object_server::SignalEmitter};
+
+use crate::ui::Action;
+
+const NAME: &str = "org.pomodoro.Timer";
+const PATH: &str = "/org/pomodoro/Timer";
+
+/// The object on the bus, with a copy of what the app is doing
+struct TimerObject {
+    ctx: egui::Context,
+    actions: Sender<Action>,
+    phase: String,
+    remaining: u64,
+    paused: bool,
+    completed_today: u32,
+}
+
+impl TimerObject {
+    fn send(&self, action: Action) {
+        let _ = self.actions.send(action);
+        // wakes the app up so the action gets handled even if the window is hidden
+        self.ctx.request_repaint();
+    }
+}
+
+#[interface(name = "org.pomodoro.Timer")]
+impl TimerObject {
+    #[zbus(property)]
+    fn phase(&self) -> String {
+        self.phase.clone()
+    }
+
+    /// In seconds, or how long it's been going for if the ring counts up
+    #[zbus(property)]
+    fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    #[zbus(property)]
+    fn paused(&self) -> bool {
+        self.paused
+    }
+
+    #[zbus(property)]
+    fn completed_today(&self) -> u32 {
+        self.completed_today
+    }
+
+    fn toggle(&self) {
+        self.send(Action::TogglePhase);
+    }
+
+    fn skip(&self) {
+        self.send(Action::NextPhase);
+    }
+
+    fn reset(&self) {
+        self.send(Action::Reset);
+    }
+
+    fn set_profile(&self, name: String) {
+        self.send(Action::SwitchProfile(name));
+    }
+
+    /// Named so it doesn't clash with the change notification for the `Phase` property
+    #[zbus(signal, name = "PhaseChanged")]
+    async fn new_phase(emitter: &SignalEmitter<'_>, phase: &str) -> zbus::Result<()>;
+}
+
+/// Puts the timer on the session bus for desktop widgets and scripts to use
+pub struct DBusService {
+    connection: Connection,
+    actions: Receiver<Action>,
+}
+
+impl DBusService {
+    pub fn new(ctx: &egui::Context) -> Option<Self> {
+        let (sender, actions) = mpsc::channel();
+        let object = TimerObject {
+            ctx: ctx.clone(),
+            actions: sender,
+            phase: String::new(),
+            remaining: 0,
+            paused: true,
+            completed_today: 0,
+        };
+        let connection = zbus::blocking::connection::Builder::session()
+            .and_then(|builder| builder.name(NAME))
+            .and_then(|builder| builder.serve_at(PATH, object))
+            .and_then(|builder| builder.build());
+        match connection {
+            Ok(connection) => Some(Self {
+                connection,
+                actions,
+            }),
+            Err(e) => {
+                println!("Could not put {NAME} on the session bus: {e}");
+                None
+            }
+        }
+    }
+
+    /// Gets called every frame, only telling the bus about the properties that changed
+    pub fn update(&self, status: &Status, completed_today: u32) -> zbus::Result<()> {
+        let iface_ref = self
+            .connection
+            .object_server()
+            .interface::<_, TimerObject>(PATH)?;
+        let emitter = iface_ref.signal_emitter();
+        let remaining = status.time.as_secs();
+        {
+            let object = iface_ref.get();
+            if object.phase == status.name
+                && object.remaining == remaining
+                && object.paused == status.paused
+                && object.completed_today == completed_today
+            {
+                return Ok(());
+            }
+        }
+        let mut object = iface_ref.get_mut();
+        if object.phase != status.name {
+            object.phase = status.name.clone();
+            zbus::block_on(object.phase_changed(emitter))?;
+            zbus::block_on(TimerObject::new_phase(emitter, &status.name))?;
+        }
+        if object.remaining != remaining {
+            object.remaining = remaining;
+            zbus::block_on(object.remaining_changed(emitter))?;
+        }
+        if object.paused != status.paused {
+            object.paused = status.paused;
+            zbus::block_on(object.paused_changed(emitter))?;
+        }
+        if object.completed_today != completed_today {
+            object.completed_today = completed_today;
+            zbus::block_on(object.completed_today_changed(emitter))?;
+        }
+        Ok(())
+    }
+
+    pub fn get_actions(&self) -> Vec<Action> {
+        self.actions.try_iter().collect()
+    }
+}
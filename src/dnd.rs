@@ -0,0 +1,133 @@
+use std::{collections::HashMap, process::Command};
+
+use pomodoro_core::config::DndBackend;
+use zbus::{blocking::Connection, zvariant::Value};
+
+const NOTIFICATIONS: &str = "org.freedesktop.Notifications";
+const GNOME_SCHEMA: &str = "org.gnome.desktop.notifications";
+
+/// Turns do not disturb on and off, putting things back how they were when it's dropped
+pub struct DoNotDisturb {
+    backend: DndBackend,
+    /// What it was last asked to be, so failing isn't retried every frame
+    wanted: bool,
+    /// If it was actually turned on, so only that gets undone
+    active: bool,
+    /// The inhibition only lasts as long as this connection
+    connection: Option<Connection>,
+    cookie: u32,
+    /// What GNOME's `show-banners` was set to before it was turned off
+    show_banners: String,
+}
+
+impl DoNotDisturb {
+    pub fn new(backend: DndBackend) -> Self {
+        Self {
+            backend,
+            wanted: false,
+            active: false,
+            connection: None,
+            cookie: 0,
+            show_banners: "true".to_string(),
+        }
+    }
+
+    /// Only does something when `wanted` is different to last time
+    pub fn set(&mut self, wanted: bool) {
+        if wanted == self.wanted {
+            return;
+        }
+        self.wanted = wanted;
+        if wanted != self.active {
+            self.switch(wanted);
+        }
+    }
+
+    fn switch(&mut self, active: bool) {
+        let result = if active {
+            self.enable()
+        } else {
+            self.disable()
+        };
+        match result {
+            Ok(()) => self.active = active,
+            Err(e) => {
+                let state = if active { "on" } else { "off" };
+                println!("Could not turn do not disturb {state}: {e}");
+            }
+        }
+    }
+
+    fn enable(&mut self) -> Result<(), String> {
+        match self.backend {
+            DndBackend::Freedesktop => {
+                let connection = Connection::session().map_err(|e| e.to_string())?;
+                let reply = connection
+                    .call_method(
+                        Some(NOTIFICATIONS),
+                        "/org/freedesktop/Notifications",
+                        Some(NOTIFICATIONS),
+                        "Inhibit",
+                        &("pomodoro", "Work phase", HashMap::<&str, Value>::new()),
+                    )
+                    .map_err(|e| e.to_string())?;
+                self.cookie = reply.body().deserialize().map_err(|e| e.to_string())?;
+                self.connection = Some(connection);
+                Ok(())
+            }
+            DndBackend::Gnome => {
+                self.show_banners = run("gsettings", &["get", GNOME_SCHEMA, "show-banners"])?;
+                run("gsettings", &["set", GNOME_SCHEMA, "show-banners", "false"]).map(|_| ())
+            }
+            DndBackend::Mako => run("makoctl", &["mode", "-a", "do-not-disturb"]).map(|_| ()),
+            DndBackend::Dunst => run("dunstctl", &["set-paused", "true"]).map(|_| ()),
+        }
+    }
+
+    fn disable(&mut self) -> Result<(), String> {
+        match self.backend {
+            DndBackend::Freedesktop => {
+                let Some(connection) = self.connection.take() else {
+                    return Ok(());
+                };
+                connection
+                    .call_method(
+                        Some(NOTIFICATIONS),
+                        "/org/freedesktop/Notifications",
+                        Some(NOTIFICATIONS),
+                        "UnInhibit",
+                        &(self.cookie),
+                    )
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+            DndBackend::Gnome => run(
+                "gsettings",
+                &["set", GNOME_SCHEMA, "show-banners", &self.show_banners],
+            )
+            .map(|_| ()),
+            DndBackend::Mako => run("makoctl", &["mode", "-r", "do-not-disturb"]).map(|_| ()),
+            DndBackend::Dunst => run("dunstctl", &["set-paused", "false"]).map(|_| ()),
+        }
+    }
+}
+
+impl Drop for DoNotDisturb {
+    fn drop(&mut self) {
+        if self.active {
+            self.switch(false);
+        }
+    }
+}
+
+/// Runs the program and returns what it printed
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{program}: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("{program}: {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,169 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use pomodoro_core::config::FocusGuard as FocusGuardConfig;
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+const BLOCK_START: &str = "# pomodoro focus guard start";
+const BLOCK_END: &str = "# pomodoro focus guard end";
+
+/// Warns about processes and blocks hostnames while working, undoing it all when it's dropped
+pub struct FocusGuard {
+    guarding: bool,
+    last_scan: Option<Instant>,
+    /// Processes that have already been warned about this work phase
+    warned: Vec<String>,
+    /// The hosts file that the hostnames were written to
+    blocked: Option<PathBuf>,
+}
+
+impl FocusGuard {
+    /// Takes out the hostnames if they were left blocked, like when the app was killed while
+    /// working
+    pub fn new(config: &FocusGuardConfig) -> Self {
+        if let Some(hosts_file) = &config.hosts_file {
+            let blocked = std::fs::read_to_string(hosts_file)
+                .is_ok_and(|contents| contents.lines().any(|line| line.trim() == BLOCK_START));
+            if blocked {
+                unblock(hosts_file);
+            }
+        }
+        Self {
+            guarding: false,
+            last_scan: None,
+            warned: vec![],
+            blocked: None,
+        }
+    }
+
+    /// Gets called every frame, returns the listed processes that were just found running
+    pub fn update(&mut self, config: &FocusGuardConfig, working: bool) -> Vec<String> {
+        if !config.enabled || !working {
+            self.stop();
+            return vec![];
+        }
+        // only tried once each work phase, so failing to write isn't retried every frame
+        if !self.guarding {
+            self.guarding = true;
+            if let Some(hosts_file) = &config.hosts_file {
+                self.block_hosts(hosts_file, &config.hostnames);
+            }
+        }
+
+        if self.last_scan.is_some_and(|t| t.elapsed() < SCAN_INTERVAL) {
+            return vec![];
+        }
+        self.last_scan = Some(Instant::now());
+        let running = running_processes();
+        let found: Vec<String> = config
+            .processes
+            .iter()
+            .filter(|p| !self.warned.contains(p) && running.iter().any(|r| is_process(r, p)))
+            .cloned()
+            .collect();
+        self.warned.extend(found.iter().cloned());
+        found
+    }
+
+    /// Unblocks the hostnames, and forgets what was warned about
+    pub fn stop(&mut self) {
+        self.guarding = false;
+        self.warned.clear();
+        self.last_scan = None;
+        self.unblock_hosts();
+    }
+
+    fn block_hosts(&mut self, hosts_file: &Path, hostnames: &[String]) {
+        // anything but it not being there yet means what's in it would be lost by writing it
+        let contents = match std::fs::read_to_string(hosts_file) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                println!("Could not read {}: {e}", hosts_file.display());
+                return;
+            }
+        };
+        let mut contents = remove_block(&contents);
+        contents.push_str(BLOCK_START);
+        contents.push('\n');
+        for hostname in hostnames {
+            contents.push_str(&format!("0.0.0.0 {hostname}\n"));
+        }
+        contents.push_str(BLOCK_END);
+        contents.push('\n');
+        match std::fs::write(hosts_file, contents) {
+            Ok(()) => self.blocked = Some(hosts_file.to_path_buf()),
+            Err(e) => println!("Could not write to {}: {e}", hosts_file.display()),
+        }
+    }
+
+    fn unblock_hosts(&mut self) {
+        if let Some(hosts_file) = self.blocked.take() {
+            unblock(&hosts_file);
+        }
+    }
+}
+
+impl Drop for FocusGuard {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn unblock(hosts_file: &Path) {
+    let Ok(contents) = std::fs::read_to_string(hosts_file) else {
+        return;
+    };
+    if let Err(e) = std::fs::write(hosts_file, remove_block(&contents)) {
+        println!("Could not write to {}: {e}", hosts_file.display());
+    }
+}
+
+/// Takes out the lines that were added, leaving everything else as it was
+fn remove_block(contents: &str) -> String {
+    let mut inside = false;
+    let mut kept = String::new();
+    for line in contents.lines() {
+        match line.trim() {
+            BLOCK_START => inside = true,
+            BLOCK_END => inside = false,
+            _ if !inside => {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+            _ => {}
+        }
+    }
+    kept
+}
+
+/// The kernel cuts process names down to 15 characters
+fn is_process(running: &str, name: &str) -> bool {
+    running == name || (running.len() == 15 && name.starts_with(running))
+}
+
+#[cfg(target_os = "linux")]
+fn running_processes() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .filter_map(|e| std::fs::read_to_string(e.path().join("comm")).ok())
+        .map(|name| name.trim_end().to_string())
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn running_processes() -> Vec<String> {
+    vec![]
+}
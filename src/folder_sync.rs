@@ -0,0 +1,316 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use pomodoro_core::{
+    config::FolderSync as FolderSyncConfig,
+    crypto::{Cost, Key},
+    sync::{
+        get_setting, merge, new_key_file, open_key_file, parse_log, synced_table, Change,
+        ConfigChange, LogEntry, Merged,
+    },
+    Config, History, Record,
+};
+use toml::Table;
+
+use crate::{history, paths};
+
+const KEY_FILE: &str = "key";
+const LOG_EXTENSION: &str = "log";
+/// How often the folder is looked at for what other devices wrote
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// A random name for this device, made the first time it's needed, which names its log
+fn get_device_id() -> Result<String, String> {
+    let file = paths::get_data_dir()
+        .ok_or("Nowhere to keep the device id")?
+        .join("device");
+    if let Ok(id) = fs::read_to_string(&file) {
+        let id = id.trim();
+        if !id.is_empty() {
+            return Ok(id.to_string());
+        }
+    }
+    let id = format!("{:016x}", u64::from_ne_bytes(random_bytes()?));
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    fs::write(&file, &id).map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+fn get_logs(folder: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == LOG_EXTENSION))
+        .collect()
+}
+
+/// Reads the key from the folder, or makes one if nothing's been written there yet
+fn open_key(folder: &Path, passphrase: Option<&str>) -> Result<Option<Key>, String> {
+    let file = folder.join(KEY_FILE);
+    match (fs::read_to_string(&file).ok(), passphrase) {
+        (None, None) => Ok(None),
+        (Some(_), None) => Err("The sync folder is encrypted, but there's no passphrase".into()),
+        (Some(contents), Some(passphrase)) => open_key_file(&contents, passphrase).map(Some),
+        (None, Some(_)) if !get_logs(folder).is_empty() => {
+            Err("The sync folder already has logs in it that aren't encrypted".into())
+        }
+        (None, Some(passphrase)) => {
+            let salt: [u8; 16] = random_bytes()?;
+            let (key, contents) =
+                new_key_file(passphrase, &salt, Cost::default(), random_bytes()?)?;
+            fs::write(&file, contents).map_err(|e| e.to_string())?;
+            Ok(Some(key))
+        }
+    }
+}
+
+/// What's needed to read and write the logs
+struct Opened {
+    folder: PathBuf,
+    device: String,
+    key: Option<Key>,
+}
+
+impl Opened {
+    /// Slow when there's a passphrase, as the key takes a while to derive
+    fn new(folder: PathBuf, passphrase: Option<&str>) -> Result<Self, String> {
+        fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+        let key = open_key(&folder, passphrase)?;
+        Ok(Self {
+            device: get_device_id()?,
+            key,
+            folder,
+        })
+    }
+
+    fn get_log_file(&self) -> PathBuf {
+        self.folder.join(format!("{}.{LOG_EXTENSION}", self.device))
+    }
+
+    fn append(&self, entry: &LogEntry) -> Result<(), String> {
+        let key = match &self.key {
+            Some(key) => Some((key, random_bytes()?)),
+            None => None,
+        };
+        let line = entry.to_line(&self.device, key)?;
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.get_log_file())
+            .and_then(|mut f| writeln!(f, "{line}"))
+            .map_err(|e| e.to_string())
+    }
+
+    fn add_record(&self, record: &Record) {
+        let entry = LogEntry {
+            at: record.at * 1000,
+            change: Change::Record(record.clone()),
+        };
+        if let Err(e) = self.append(&entry) {
+            println!("Could not add to {}: {e}", self.folder.display());
+        }
+    }
+
+    fn read(&self) -> Result<Merged, String> {
+        let mut logs = vec![];
+        for file in get_logs(&self.folder) {
+            let device = file.file_stem().unwrap_or_default().to_string_lossy();
+            let log = fs::read_to_string(&file).map_err(|e| e.to_string())?;
+            let (entries, unreadable) = parse_log(&log, &device, self.key.as_ref());
+            if unreadable > 0 {
+                println!("{unreadable} lines in {} can't be read", file.display());
+            }
+            logs.push((device.into_owned(), entries));
+        }
+        Ok(merge(logs))
+    }
+}
+
+/// Shares the history and config with other devices through a folder
+pub struct FolderSync {
+    folder: PathBuf,
+    /// The folder gets opened on another thread, as deriving the key takes a while
+    opening: Option<Receiver<Result<Opened, String>>>,
+    opened: Option<Opened>,
+    /// Records from while it was being opened
+    waiting: Vec<Record>,
+    /// The synced part of the config as of when the logs were last merged
+    config: Option<Table>,
+    /// How big the logs were and when they were changed, so they're only read again once they
+    /// change
+    seen: Vec<(PathBuf, u64, Option<SystemTime>)>,
+    last_check: Option<Instant>,
+}
+
+impl FolderSync {
+    pub fn new(config: &FolderSyncConfig) -> Option<Self> {
+        let folder = config.folder.clone()?;
+        let (sender, opening) = mpsc::channel();
+        let (opened_folder, passphrase) = (folder.clone(), config.passphrase.clone());
+        thread::spawn(move || {
+            let _ = sender.send(Opened::new(opened_folder, passphrase.as_deref()));
+        });
+        Some(Self {
+            folder,
+            opening: Some(opening),
+            opened: None,
+            waiting: vec![],
+            config: None,
+            seen: vec![],
+            last_check: None,
+        })
+    }
+
+    fn finish_opening(&mut self, result: Result<Opened, String>) {
+        self.opening = None;
+        match result {
+            Ok(opened) => {
+                let mut records = std::mem::take(&mut self.waiting);
+                // everything from before syncing was turned on, which should have the waiting
+                // records in it too
+                if !opened.get_log_file().exists() {
+                    let history = history::load().records;
+                    records.retain(|r| !history.contains(r));
+                    records.splice(0..0, history);
+                }
+                for record in records {
+                    opened.add_record(&record);
+                }
+                self.opened = Some(opened);
+            }
+            Err(e) => println!("Could not sync with {}: {e}", self.folder.display()),
+        }
+    }
+
+    /// `None` if the folder isn't ready to be used yet
+    fn get_opened(&mut self) -> Option<&Opened> {
+        if let Some(result) = self.opening.as_ref().and_then(|r| r.try_recv().ok()) {
+            self.finish_opening(result);
+        }
+        self.opened.as_ref()
+    }
+
+    pub fn is_open(&mut self) -> bool {
+        self.get_opened().is_some()
+    }
+
+    /// Blocks until the folder is opened, for the command line
+    pub fn wait(&mut self) {
+        if let Some(result) = self.opening.as_ref().and_then(|r| r.recv().ok()) {
+            self.finish_opening(result);
+        }
+    }
+
+    pub fn add_record(&mut self, record: &Record) {
+        if let Some(opened) = self.get_opened() {
+            opened.add_record(record);
+        } else if self.opening.is_some() {
+            self.waiting.push(record.clone());
+        }
+    }
+
+    /// Everyone's history, or just this device's if the folder can't be read yet
+    pub fn load_history(&mut self) -> History {
+        let Some(opened) = self.get_opened() else {
+            return history::load();
+        };
+        opened
+            .read()
+            .map(|merged| merged.history)
+            .unwrap_or_else(|e| {
+                println!("Could not read {}: {e}", opened.folder.display());
+                history::load()
+            })
+    }
+
+    /// If any of the logs changed since this was last called
+    fn has_changed(&mut self) -> bool {
+        let seen: Vec<_> = get_logs(&self.folder)
+            .into_iter()
+            .map(|file| {
+                let metadata = fs::metadata(&file).ok();
+                let length = metadata.as_ref().map_or(0, |m| m.len());
+                let modified = metadata.and_then(|m| m.modified().ok());
+                (file, length, modified)
+            })
+            .collect();
+        let changed = seen != self.seen;
+        self.seen = seen;
+        changed
+    }
+
+    /// Writes what changed in the config since last time to this device's log, giving back what
+    /// other devices changed in the meantime. The first time, what's already in the folder wins
+    pub fn update(&mut self, config: &Config) -> Result<Option<ConfigChange>, String> {
+        if !self.is_open()
+            || self
+                .last_check
+                .is_some_and(|t| t.elapsed() < CHECK_INTERVAL)
+        {
+            return Ok(None);
+        }
+        self.last_check = Some(Instant::now());
+        let local = synced_table(config)?;
+        let before = self.config.clone().unwrap_or_default();
+        let changed = self.has_changed();
+        let Some(opened) = &self.opened else {
+            return Ok(None);
+        };
+        let mut merged = if changed || self.config.is_none() {
+            opened.read()?.config
+        } else {
+            before.clone()
+        };
+        let (mut remote, local_change) = if self.config.is_none() {
+            // only what the folder doesn't have yet gets added to it
+            let mut remote = ConfigChange::between(&local, &merged);
+            remote.retain(|_, value| value.is_some());
+            let mut local_change = ConfigChange::between(&merged, &local);
+            local_change
+                .retain(|path, value| value.is_some() && get_setting(&merged, path).is_none());
+            (remote, local_change)
+        } else {
+            (
+                ConfigChange::between(&before, &merged),
+                ConfigChange::between(&before, &local),
+            )
+        };
+        // what was changed here is newer than what came in
+        remote.retain(|path, value| {
+            !local_change.touches(path) && get_setting(&local, path) != value
+        });
+
+        if !local_change.is_empty() {
+            opened.append(&LogEntry {
+                at: now_millis(),
+                change: Change::Config(local_change.clone()),
+            })?;
+            local_change.apply_to_table(&mut merged);
+        }
+        self.config = Some(merged);
+        Ok((!remote.is_empty()).then_some(remote))
+    }
+}
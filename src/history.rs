@@ -0,0 +1,66 @@
+use std::{io::Write, path::PathBuf};
+
+use pomodoro_core::{History, Record};
+
+use crate::paths;
+
+fn get_history_file() -> Option<PathBuf> {
+    let file = paths::get_data_dir()?.join("history.toml");
+    // it used to be kept with the config
+    let old = paths::get_old_dir().map(|dir| dir.join("history.toml"));
+    if let Some(old) = old.filter(|old| !file.exists() && old.is_file() && *old != file) {
+        if let Some(dir) = file.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Err(e) = std::fs::rename(&old, &file) {
+            println!(
+                "Could not move {} to {}: {e}",
+                old.display(),
+                file.display()
+            );
+            return Some(old);
+        }
+    }
+    Some(file)
+}
+
+/// Everything recorded so far, which is empty if there's no file yet
+pub fn load() -> History {
+    let Some(file) = get_history_file() else {
+        return History::default();
+    };
+    let Ok(data) = std::fs::read_to_string(&file) else {
+        return History::default();
+    };
+    toml::from_str(&data).unwrap_or_else(|e| {
+        println!("Could not read {}: {e}", file.display());
+        History::default()
+    })
+}
+
+/// Adds the record to the end of the file, each one is its own `[[record]]` table so nothing
+/// already there needs to be read or rewritten
+pub fn append(record: Record) {
+    let Some(file) = get_history_file() else {
+        return;
+    };
+    let history = History {
+        records: vec![record],
+    };
+    let result = toml::to_string(&history)
+        .map_err(|e| e.to_string())
+        .and_then(|data| {
+            if let Some(dir) = file.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&file)
+                .and_then(|mut f| f.write_all(data.as_bytes()))
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        println!("Could not write to {}: {e}", file.display());
+    }
+}
@@ -0,0 +1,40 @@
+use std::process::Command;
+
+use pomodoro_core::{Config, Event};
+
+/// Runs the hook for each of the events, without waiting for them to finish
+pub fn run_hooks(events: Vec<Event>, config: &Config) {
+    for event in events {
+        if let Some(command) = config.hooks.get(event.kind) {
+            run_hook(command, &event, config.task.as_deref());
+        }
+    }
+}
+
+fn run_hook(command: &str, event: &Event, task: Option<&str>) {
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    cmd.env("POMODORO_EVENT", event.kind.get_name())
+        .env("POMODORO_PHASE", event.phase)
+        .env("POMODORO_DURATION", event.duration.as_secs().to_string())
+        .env("POMODORO_CYCLE", event.cycle.to_string())
+        .env("POMODORO_TASK", task.unwrap_or_default());
+
+    let command = command.to_string();
+    // waits on another thread so the process gets cleaned up without blocking the ui
+    std::thread::spawn(move || match cmd.status() {
+        Ok(status) if !status.success() => println!("Hook `{command}` failed: {status}"),
+        Err(e) => println!("Could not run hook `{command}`: {e}"),
+        _ => {}
+    });
+}
@@ -0,0 +1,285 @@
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui;
+use pomodoro_core::{
+    api::Status,
+    instance::{get_name, Command, PortFile, ACKNOWLEDGE},
+    utils::is_token,
+};
+
+use crate::{paths, ui::Action};
+
+/// How long to wait on an instance that might not be there anymore
+const TIMEOUT: Duration = Duration::from_millis(500);
+/// How long another instance that has the lock gets to start listening
+const STARTUP_TIME: Duration = Duration::from_secs(5);
+
+/// The state directory file for the instance using `config_file`, which is what's run on its own
+fn get_file(config_file: &Path, extension: &str) -> Option<PathBuf> {
+    let config_file = std::path::absolute(config_file).unwrap_or(config_file.to_path_buf());
+    let name = get_name(&config_file);
+    paths::get_state_dir().map(|dir| dir.join(format!("{name}.{extension}")))
+}
+
+/// Held locked by the running instance, the OS lets go of it if that crashes
+fn get_lock_file(config_file: &Path) -> Option<PathBuf> {
+    get_file(config_file, "lock")
+}
+
+/// Has the port the running instance listens on. It's not in the lock file, as that can't be
+/// read by anything else while it's locked on Windows
+fn get_port_file(config_file: &Path) -> Option<PathBuf> {
+    get_file(config_file, "port")
+}
+
+/// Sends the commands to the instance that's already running, giving back what it answered
+/// with, or `None` if there isn't one, which is also the case if it crashed and left the lock
+/// file behind
+fn send(config_file: &Path, commands: &[Command]) -> Option<Vec<String>> {
+    let port_file = get_port_file(config_file)
+        .and_then(|file| std::fs::read_to_string(file).ok())
+        .and_then(|contents| PortFile::parse(&contents))?;
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port_file.port));
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT).ok()?;
+    let _ = stream.set_read_timeout(Some(TIMEOUT));
+    let message: String = std::iter::once(port_file.token)
+        .chain(commands.iter().map(Command::to_string))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    stream.write_all(message.as_bytes()).ok()?;
+    stream.shutdown(Shutdown::Write).ok()?;
+    let mut answer = vec![];
+    for line in BufReader::new(stream).lines() {
+        let line = line.ok()?;
+        if line == ACKNOWLEDGE {
+            return Some(answer);
+        }
+        answer.push(line);
+    }
+    None
+}
+
+/// Hands the commands to the instance that's already running and brings it to the front, or
+/// returns false if there isn't one
+fn forward(config_file: &Path, commands: &[Command]) -> bool {
+    let mut commands = commands.to_vec();
+    commands.push(Command::Show);
+    send(config_file, &commands).is_some()
+}
+
+/// What the instance using `config_file` is doing, or `None` if there isn't one
+pub fn query_status(config_file: &Path) -> Option<String> {
+    send(config_file, &[Command::Status]).map(|answer| answer.join("\n"))
+}
+
+/// What happened when trying to become the running instance
+pub enum Launch {
+    /// Nothing else is running, `None` if it couldn't be locked so this runs on its own
+    First(Option<Lock>),
+    /// The commands were handed to the instance that's already running
+    Forwarded,
+    /// Another instance has the lock, but isn't answering
+    NotAnswering,
+}
+
+/// Becomes the running instance for `config_file`, or forwards the commands to the one that
+/// already is. When two are started at once only one gets the lock, and the other waits for it
+/// to start listening
+pub fn launch(config_file: &Path, commands: &[Command]) -> Launch {
+    let started = Instant::now();
+    loop {
+        if forward(config_file, commands) {
+            return Launch::Forwarded;
+        }
+        match Lock::new(config_file) {
+            Ok(lock) => return Launch::First(lock),
+            Err(()) if started.elapsed() < STARTUP_TIME => thread::sleep(TIMEOUT / 5),
+            Err(()) => return Launch::NotAnswering,
+        }
+    }
+}
+
+/// Being the running instance, which lasts until this is dropped
+pub struct Lock {
+    listener: TcpListener,
+    port_file: PathBuf,
+    /// What later launches have to send first
+    token: String,
+    _file: File,
+}
+
+impl Lock {
+    /// `Err` if another instance has the lock
+    fn new(config_file: &Path) -> Result<Option<Self>, ()> {
+        let (Some(lock_file), Some(port_file)) =
+            (get_lock_file(config_file), get_port_file(config_file))
+        else {
+            return Ok(None);
+        };
+        if let Some(dir) = lock_file.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_file);
+        let locked = file.map_err(TryLockError::Error).and_then(|file| {
+            file.try_lock()?;
+            Ok(file)
+        });
+        let file = match locked {
+            Ok(file) => file,
+            Err(TryLockError::WouldBlock) => return Err(()),
+            Err(TryLockError::Error(e)) => {
+                println!("Could not lock {}: {e}", lock_file.display());
+                return Ok(None);
+            }
+        };
+        let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, 0)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                println!("Could not listen for other instances: {e}");
+                return Ok(None);
+            }
+        };
+        let Some(port) = listener.local_addr().ok().map(|a| a.port()) else {
+            return Ok(None);
+        };
+        let mut token = [0; 16];
+        if let Err(e) = getrandom::getrandom(&mut token) {
+            println!("Could not make a token for other instances: {e}");
+            return Ok(None);
+        }
+        let contents = PortFile {
+            port,
+            token: token.iter().map(|b| format!("{b:02x}")).collect(),
+        };
+        if let Err(e) = write_private(&port_file, &contents.to_string()) {
+            println!("Could not write {}: {e}", port_file.display());
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            listener,
+            port_file,
+            token: contents.token,
+            _file: file,
+        }))
+    }
+}
+
+/// Only readable by this user, and written next to it first so it's never read half written
+fn write_private(file: &Path, contents: &str) -> std::io::Result<()> {
+    let temp_file = file.with_extension("tmp");
+    let mut options = OpenOptions::new();
+    options.create(true).truncate(true).write(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(&temp_file)
+        .and_then(|mut f| f.write_all(contents.as_bytes()))
+        .and_then(|_| std::fs::rename(&temp_file, file))
+}
+
+impl Drop for Lock {
+    // the lock is still held here, so this can't remove another instance's port
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.port_file);
+    }
+}
+
+/// Turns what later launches forward into actions, and answers them with the status
+pub struct Instance {
+    actions: Receiver<Action>,
+    status: Arc<Mutex<Option<Status>>>,
+    _lock: Option<Lock>,
+}
+
+impl Instance {
+    /// `startup` is handled first, for what this one was started with
+    pub fn new(ctx: &egui::Context, lock: Option<Lock>, startup: Vec<Action>) -> Self {
+        let (sender, actions) = mpsc::channel();
+        for action in startup {
+            let _ = sender.send(action);
+        }
+        let status = Arc::new(Mutex::new(None));
+        if let Some(lock) = &lock {
+            if let Ok(listener) = lock.listener.try_clone() {
+                let (ctx, status, token) = (ctx.clone(), status.clone(), lock.token.clone());
+                thread::spawn(move || {
+                    for stream in listener.incoming().flatten() {
+                        handle(stream, &token, &sender, &status, &ctx);
+                    }
+                });
+            }
+        }
+        Self {
+            actions,
+            status,
+            _lock: lock,
+        }
+    }
+
+    /// Gets called every frame with what the timer is doing
+    pub fn update(&self, status: Status) {
+        *self.status.lock().unwrap() = Some(status);
+    }
+
+    pub fn get_actions(&self) -> Vec<Action> {
+        self.actions.try_iter().collect()
+    }
+}
+
+pub fn to_action(command: Command) -> Option<Action> {
+    let action = match command {
+        Command::Show => Action::ShowWindow,
+        Command::Start => Action::StartPhase,
+        Command::Toggle => Action::TogglePhase,
+        Command::Skip => Action::NextPhase,
+        Command::Profile(name) => Action::SwitchProfile(name),
+        Command::Status => return None,
+    };
+    Some(action)
+}
+
+/// Anything that doesn't start with the token is dropped without an answer
+fn handle(
+    stream: TcpStream,
+    token: &str,
+    actions: &Sender<Action>,
+    status: &Mutex<Option<Status>>,
+    ctx: &egui::Context,
+) {
+    let _ = stream.set_read_timeout(Some(TIMEOUT));
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut lines = BufReader::new(stream).lines().map_while(Result::ok);
+    if !lines.next().is_some_and(|line| is_token(&line, token)) {
+        return;
+    }
+    let commands: Vec<Command> = lines.filter_map(|line| Command::parse(&line)).collect();
+    for command in commands {
+        if command == Command::Status {
+            let status = status.lock().unwrap().clone();
+            let status = status.map_or("Starting".to_string(), |s| s.to_string());
+            let _ = writeln!(writer, "{status}");
+        } else if let Some(action) = to_action(command) {
+            let _ = actions.send(action);
+        }
+    }
+    // wakes the app up so the commands get handled even if the window is hidden
+    ctx.request_repaint();
+    let _ = writeln!(writer, "{ACKNOWLEDGE}");
+}
@@ -1,45 +1,104 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
-use std::time::Duration;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 use config::ConfigManager;
 use eframe::{
     egui::{self, ViewportBuilder},
-    epaint::{Color32, Vec2},
-    Theme,
+    epaint::{Color32, Pos2, Vec2},
 };
 
 use notifications::{draw_notification, Notification};
 
+use pomodoro_core::{
+    api::Status,
+    args::{Args, USAGE},
+    config::{Mode, Theme},
+    schedule::LocalTime,
+    Entry, NamedTimer, Percent, PomodoroPhase, Record, Stopwatch, Timer,
+};
+
+mod api;
+mod break_screen;
+mod cli;
 mod precomputed;
-mod timer;
-use timer::PomodoroPhase;
 mod ui;
 mod utils;
-use ui::Action;
-use utils::{play_sound, Percent};
+use ui::{Action, WindowLayout};
+use utils::{play_sound, play_sound_file};
 mod circle_widget;
 mod notifications;
-use circle_widget::ProgressCircle;
-mod stats;
-use stats::Stats;
+mod paths;
+use circle_widget::{ProgressCircle, RingTimer};
 mod config;
+#[cfg(target_os = "linux")]
+mod dbus;
+#[cfg(target_os = "linux")]
+mod dnd;
+mod focus_guard;
+mod folder_sync;
+mod history;
+mod hooks;
+mod instance;
+mod mqtt;
+mod team;
+mod theme;
+mod timers;
+#[cfg(target_os = "linux")]
+mod tray;
+mod viewport;
 
-#[cfg(windows)]
-use utils::get_window_size;
-use utils::MonitorSize;
+use utils::{get_window_size, MonitorSize};
 
+const POSTPONE_TIME: Duration = Duration::from_secs(5 * 60);
 const RAW_COMPLETE_SOUND: &[u8; 368684] = include_bytes!("./assets/completed.wav");
 
 struct App {
-    phase: PomodoroPhase,
-    stats: Stats,
+    timer: Timer,
+    timers: Vec<timers::ExtraTimer>,
+    countdown: NamedTimer,
+    stopwatch: Stopwatch,
     notifications: Vec<Notification>,
     screen_size: MonitorSize,
     config_manager: ConfigManager,
+    compact: bool,
+    viewport: ViewportBuilder,
+    #[cfg(target_os = "linux")]
+    tray: Option<tray::Tray>,
+    #[cfg(target_os = "linux")]
+    dnd: Option<dnd::DoNotDisturb>,
+    focus_guard: focus_guard::FocusGuard,
+    /// When the break screen comes back after being postponed
+    postponed_until: Option<Instant>,
+    postpones_used: u32,
+    /// When the eye reminder that's showing runs out
+    reminder_until: Option<Instant>,
+    reminders_given: u32,
+    /// When the schedule was last looked at, so auto starts in between aren't missed
+    schedule_checked: Option<LocalTime>,
+    team: Option<team::Session>,
+    api: Option<api::Api>,
+    mqtt: Option<mqtt::Mqtt>,
+    folder_sync: Option<folder_sync::FolderSync>,
+    instance: instance::Instance,
+    #[cfg(target_os = "linux")]
+    dbus: Option<dbus::DBusService>,
+    /// Work phases finished today, and when today started
+    #[cfg(target_os = "linux")]
+    completed_today: (u64, u32),
+    quitting: bool,
+    announcement: String,
 }
 
 impl App {
-    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(
+        cc: &eframe::CreationContext<'_>,
+        config_manager: ConfigManager,
+        lock: Option<instance::Lock>,
+        startup: Vec<Action>,
+    ) -> Self {
         // Customize egui here with cc.egui_ctx.set_fonts and cc.egui_ctx.set_visuals.
         // Restore app state using cc.storage (requires the "persistence" feature).
         // Use the cc.gl (a glow::Context) to create graphics shaders and buffers that you can use
@@ -50,100 +109,810 @@ impl App {
             size.height /= cc.egui_ctx.pixels_per_point();
             size
         };
-        let mut config_manager = ConfigManager::new();
-        config_manager.load_blocking();
+        if let Some(font) = config_manager.get_theme().font {
+            theme::load_font(&cc.egui_ctx, &font);
+        }
+        let viewport = Self::get_full_viewport(&config_manager);
+        let timer = Timer::new(&config_manager.config.lock().unwrap());
+        #[cfg(target_os = "linux")]
+        let tray = tray::Tray::new(&cc.egui_ctx, config_manager.get_profile_names());
+        #[cfg(target_os = "linux")]
+        let dnd = config_manager
+            .config
+            .lock()
+            .unwrap()
+            .do_not_disturb
+            .map(dnd::DoNotDisturb::new);
+        let timers = config_manager
+            .get_timers()
+            .into_iter()
+            .map(timers::ExtraTimer::new)
+            .collect();
+        let countdown = NamedTimer::new("Countdown", config_manager.get_countdown_time());
+        let stopwatch = Stopwatch::new(config_manager.get_lap_length());
+        let team = team::Session::new(&config_manager.config.lock().unwrap().team_sync);
+        let api = api::Api::new(
+            &cc.egui_ctx,
+            &config_manager.config.lock().unwrap().http_api,
+        );
+        let mqtt = mqtt::Mqtt::new(&cc.egui_ctx, &config_manager.config.lock().unwrap().mqtt);
+        let folder_sync =
+            folder_sync::FolderSync::new(&config_manager.config.lock().unwrap().folder_sync);
+        let focus_guard =
+            focus_guard::FocusGuard::new(&config_manager.config.lock().unwrap().focus_guard);
+        let instance = instance::Instance::new(&cc.egui_ctx, lock, startup);
+        #[cfg(target_os = "linux")]
+        let dbus = dbus::DBusService::new(&cc.egui_ctx);
         App {
-            phase: PomodoroPhase::new_work(config_manager.get_work_time()),
-            stats: Stats::default(),
+            timer,
+            timers,
+            countdown,
+            stopwatch,
             notifications: vec![],
             screen_size: size,
             config_manager,
+            compact: false,
+            viewport,
+            #[cfg(target_os = "linux")]
+            tray,
+            #[cfg(target_os = "linux")]
+            dnd,
+            focus_guard,
+            postponed_until: None,
+            postpones_used: 0,
+            reminder_until: None,
+            reminders_given: 0,
+            schedule_checked: None,
+            team,
+            api,
+            mqtt,
+            folder_sync,
+            instance,
+            #[cfg(target_os = "linux")]
+            dbus,
+            #[cfg(target_os = "linux")]
+            completed_today: (0, 0),
+            quitting: false,
+            announcement: String::new(),
         }
     }
 
-    fn check_time(&mut self) {
-        if let Some(start) = self.phase.get_start() {
-            if start.elapsed() > self.phase.get_duration() {
-                self.next_phase(false);
+    fn handle_action(&mut self, ctx: &egui::Context, action: Action) {
+        match action {
+            Action::NextPhase => self.next_phase(true),
+            Action::EndWork => self.next_phase(false),
+            Action::StartPhase => self.timer.start(),
+            Action::TogglePhase => self.timer.toggle(),
+            Action::JumpToStep(i) => {
+                self.timer
+                    .jump_to(i, &self.config_manager.config.lock().unwrap());
+                self.announcement = format!("{} ready", RingTimer::get_name(&self.timer));
+            }
+            Action::ToggleCompact => self.toggle_compact(ctx),
+            Action::Lap => self.stopwatch.lap(),
+            Action::Reset => match self.config_manager.get_mode() {
+                Mode::Pomodoro | Mode::Flowtime => self
+                    .timer
+                    .reset(&self.config_manager.config.lock().unwrap()),
+                Mode::Countdown => self.countdown.reset(),
+                Mode::Stopwatch => self.reset_stopwatch(),
+            },
+            Action::PostponeBreak => {
+                // the break waits, rather than running out while working
+                self.timer.pause();
+                self.postponed_until = Some(Instant::now() + POSTPONE_TIME);
+                self.postpones_used += 1;
+            }
+            Action::AcknowledgeReminder => self.notifications.retain(|n| !n.countdown),
+            Action::ToggleTimer(i) => {
+                if let Some(timer) = self.timers.get_mut(i) {
+                    timer.timer.toggle();
+                }
+            }
+            Action::ResetTimer(i) => {
+                if let Some(timer) = self.timers.get_mut(i) {
+                    timer.timer.reset();
+                }
+            }
+            Action::RemoveTimer(i) => {
+                if i < self.timers.len() {
+                    self.timers.remove(i);
+                    self.config_manager.remove_timer(i);
+                    self.config_manager.save();
+                }
+            }
+            Action::AddTimer(config) => {
+                self.config_manager.add_timer(config.clone());
+                self.config_manager.save();
+                self.timers.push(timers::ExtraTimer::new(config));
+            }
+            Action::SwitchProfile(name) => {
+                if self.config_manager.set_profile(&name) {
+                    self.config_manager.save();
+                }
+            }
+            Action::ShowWindow => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+            Action::Quit => {
+                self.quitting = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            Action::ModifyWorkPhaseConfig(d) => self.config_manager.set_work_time(d),
+            Action::ModifyBreakPhaseConfig(d) => self.config_manager.set_break_time(d),
+            Action::ModifyMode(mode) => {
+                self.config_manager.set_mode(mode);
+                // the work phase is open ended in flowtime, and has a length otherwise
+                let open_ended = self.timer.get_phase().is_open_ended();
+                if matches!(self.timer.get_phase(), PomodoroPhase::Work { .. })
+                    && open_ended != (mode == Mode::Flowtime)
+                {
+                    self.timer
+                        .reset(&self.config_manager.config.lock().unwrap());
+                }
+            }
+            Action::ModifyFlowtimeRatio(ratio) => self.config_manager.set_flowtime_ratio(ratio),
+            Action::ModifyCountdownTime(d) => {
+                self.config_manager.set_countdown_time(d);
+                self.countdown = NamedTimer::new("Countdown", d);
+            }
+            Action::ModifyLapLength(d) => {
+                self.config_manager.set_lap_length(d);
+                self.reset_stopwatch();
+            }
+            Action::ModifyThemeMode(mode) => self.config_manager.set_theme_mode(mode),
+            Action::ModifyReduceMotion(reduce) => self.config_manager.set_reduce_motion(reduce),
+            Action::None => {}
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn update_tray(&mut self, ctx: &egui::Context) {
+        let Some(tray) = &mut self.tray else {
+            return;
+        };
+        tray.update(
+            &self.timer,
+            self.config_manager.get_profile(),
+            &self.config_manager.get_theme(),
+        );
+        for action in tray.get_actions() {
+            self.handle_action(ctx, action);
+        }
+
+        // closing the window just hides it, the tray is used to quit
+        if ctx.input(|i| i.viewport().close_requested()) && !self.quitting {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        }
+    }
+
+    /// Handles what later launches asked for, and lets them know what the ring is doing
+    fn update_instance(&mut self, ctx: &egui::Context, theme: &Theme) {
+        let status = self.get_status(theme);
+        self.instance.update(status);
+        for action in self.instance.get_actions() {
+            self.handle_action(ctx, action);
+        }
+    }
+
+    /// If a work phase is running
+    fn is_working(&self) -> bool {
+        matches!(self.timer.get_phase(), PomodoroPhase::Work { .. }) && !self.timer.is_paused()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn update_dnd(&mut self) {
+        let working = self.is_working();
+        if let Some(dnd) = &mut self.dnd {
+            dnd.set(working);
+        }
+    }
+
+    fn update_focus_guard(&mut self) {
+        let working = self.is_working();
+        let found = self.focus_guard.update(
+            &self.config_manager.config.lock().unwrap().focus_guard,
+            working,
+        );
+        for process in found {
+            let text = format!("{process} is running");
+            self.notifications.push(Notification::new(text.clone()));
+            self.announcement = text;
+        }
+    }
+
+    fn update_break_screen(&mut self, ctx: &egui::Context) {
+        let config = self.config_manager.get_break_screen();
+        if !config.enabled || !matches!(self.timer.get_phase(), PomodoroPhase::Break { .. }) {
+            return;
+        }
+        if let Some(until) = self.postponed_until {
+            if Instant::now() < until {
+                return;
             }
+            self.postponed_until = None;
+            self.timer.start();
         }
+        let suggestion = break_screen::get_suggestion(&config.suggestions, &self.timer);
+        let postpones_left = config.max_postpones.saturating_sub(self.postpones_used);
+        let action = break_screen::draw_break_screen(ctx, &self.timer, suggestion, postpones_left);
+        self.handle_action(ctx, action);
     }
 
-    fn play_completed_sound() {
-        play_sound(RAW_COMPLETE_SOUND)
+    fn update_reminders(&mut self) {
+        if let Some(until) = self.reminder_until {
+            let now = Instant::now();
+            if now >= until {
+                self.notifications.retain(|n| !n.countdown);
+                self.finish_reminder(false);
+            } else if !self.notifications.iter().any(|n| n.countdown) {
+                // clicked away before it ran out
+                self.finish_reminder(true);
+            }
+        }
+
+        let config = self.config_manager.get_eye_reminders();
+        let elapsed = self.timer.time_elapsed().unwrap_or_default();
+        if self.reminder_until.is_none()
+            && matches!(self.timer.get_phase(), PomodoroPhase::Work { .. })
+            && config.is_due(elapsed, self.reminders_given)
+        {
+            // skips any that were missed, like while it was paused
+            self.reminders_given = (elapsed.as_nanos() / config.interval.as_nanos()) as u32;
+            self.reminder_until = Some(Instant::now() + config.length);
+            self.notifications.push(Notification::with_countdown(
+                config.message.clone(),
+                config.length,
+            ));
+            self.announcement = config.message;
+        }
     }
 
-    fn next_phase(&mut self, skipped: bool) {
-        Self::play_completed_sound();
-        self.phase = match self.phase {
-            PomodoroPhase::Work { .. } => {
-                if !skipped {
-                    self.notifications
-                        .push(Notification::new("Work Done!".to_string()));
+    /// Starts work when the schedule says to, and keeps phases inside working hours
+    fn update_schedule(&mut self) {
+        let schedule = self.config_manager.get_schedule();
+        let timer_mode = matches!(
+            self.config_manager.get_mode(),
+            Mode::Pomodoro | Mode::Flowtime
+        );
+        if !schedule.enabled || !timer_mode {
+            self.schedule_checked = None;
+            return;
+        }
+        let now = utils::local_time();
+        if let Some(last) = self.schedule_checked {
+            if schedule.auto_starts_between(last, now) && self.timer.is_paused() {
+                if matches!(self.timer.get_phase(), PomodoroPhase::Break { .. }) {
+                    self.next_phase(true);
                 }
-                PomodoroPhase::new_break(self.config_manager.get_break_time())
-            }
-            PomodoroPhase::Break { .. } => {
-                if !skipped {
-                    self.notifications
-                        .push(Notification::new("Break Done!".to_string()));
+                if let Some(left) = schedule.time_left(now).filter(|_| schedule.shorten_phases) {
+                    self.timer.shorten_to(left);
                 }
-                PomodoroPhase::new_work(self.config_manager.get_work_time())
+                self.timer.start();
+                self.notifications
+                    .push(Notification::new("Work started on schedule".to_string()));
+                self.announcement = "Work started on schedule".to_string();
+            }
+        }
+        self.schedule_checked = Some(now);
+        if let Some(left) = schedule.time_left(now).filter(|_| schedule.shorten_phases) {
+            self.timer.shorten_to(left);
+        }
+    }
+
+    /// What the ring is doing, for the API and MQTT
+    fn get_status(&mut self, theme: &Theme) -> Status {
+        let mode = self.config_manager.get_mode();
+        let cycles = self.timer.get_stats().get_count();
+        let (_, ring) = self.get_ring(theme);
+        Status {
+            mode,
+            name: ring.get_name().to_string(),
+            paused: ring.is_paused(),
+            time: ring.get_time(),
+            counts_up: ring.counts_up(),
+            cycles,
+        }
+    }
+
+    /// Counted from the history again when the day changes
+    #[cfg(target_os = "linux")]
+    fn get_completed_today(&mut self) -> u32 {
+        let today = utils::start_of_today();
+        if self.completed_today.0 != today {
+            let history = match &mut self.folder_sync {
+                Some(sync) => sync.load_history(),
+                None => history::load(),
+            };
+            let count = history.completed_work_since(today);
+            self.completed_today = (today, count as u32);
+        }
+        self.completed_today.1
+    }
+
+    #[cfg(target_os = "linux")]
+    fn update_dbus(&mut self, ctx: &egui::Context, theme: &Theme) {
+        if self.dbus.is_none() {
+            return;
+        }
+        let status = self.get_status(theme);
+        let completed_today = self.get_completed_today();
+        let Some(dbus) = &self.dbus else {
+            return;
+        };
+        if let Err(e) = dbus.update(&status, completed_today) {
+            println!("Could not update the D-Bus object: {e}");
+        }
+        for action in dbus.get_actions() {
+            self.handle_action(ctx, action);
+        }
+    }
+
+    /// Publishes what the ring is doing, and handles the commands that came in
+    fn update_mqtt(&mut self, ctx: &egui::Context, theme: &Theme) {
+        if self.mqtt.is_none() {
+            return;
+        }
+        let status = self.get_status(theme);
+        let Some(mqtt) = &mut self.mqtt else {
+            return;
+        };
+        mqtt.update(status);
+        for action in mqtt.get_actions() {
+            self.handle_action(ctx, action);
+        }
+    }
+
+    /// Tells the API what the ring is doing, and handles what it was asked to do
+    fn update_api(&mut self, ctx: &egui::Context, theme: &Theme) {
+        if self.api.is_none() {
+            return;
+        }
+        let status = self.get_status(theme);
+        let Some(api) = &self.api else {
+            return;
+        };
+        api.update(status);
+        let actions = api.get_actions();
+        let save = actions.iter().any(|action| {
+            matches!(
+                action,
+                Action::ModifyWorkPhaseConfig(_) | Action::ModifyBreakPhaseConfig(_)
+            )
+        });
+        for action in actions {
+            self.handle_action(ctx, action);
+        }
+        if save {
+            self.config_manager.save();
+        }
+    }
+
+    /// Sends the phase to the team, or takes it on from the host
+    fn update_team(&mut self) {
+        let mirror = match &mut self.team {
+            Some(team::Session::Host(host)) => {
+                host.update(&self.timer);
+                return;
             }
+            Some(team::Session::Client(client)) => client.update(),
+            None => return,
         };
-        self.stats.increment();
+        let Some(mirror) = mirror else {
+            return;
+        };
+        let same_kind =
+            std::mem::discriminant(&mirror.phase) == std::mem::discriminant(self.timer.get_phase());
+        // the host moved on first, so it gets finished here the same as if it ran out
+        if mirror.following && !same_kind {
+            self.next_phase(false);
+        }
+        self.timer.mirror(mirror.phase);
     }
-}
 
-impl eframe::App for App {
-    // This will get called every time the app updates, or every 5ms, which ever is faster
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    /// Sends config changes to the sync folder, and takes the ones other devices made
+    fn update_folder_sync(&mut self) {
+        let Some(sync) = &mut self.folder_sync else {
+            return;
+        };
+        #[cfg(target_os = "linux")]
+        let opening = !sync.is_open();
+        let result = sync
+            .update(&self.config_manager.get_saved_config())
+            .and_then(|change| match change {
+                Some(change) => self.config_manager.apply_synced(&change),
+                None => Ok(()),
+            });
+        if let Err(e) = result {
+            println!("Could not sync the config: {e}");
+        }
+        // counted again now the other devices' history can be read
+        #[cfg(target_os = "linux")]
+        if opening && self.folder_sync.as_mut().is_some_and(|s| s.is_open()) {
+            self.completed_today.0 = 0;
+        }
+    }
+
+    /// Keeps it in the history, and in the sync folder if there is one
+    fn record(&mut self, entry: Entry) {
+        let record = Record::now(entry);
+        if let Some(sync) = &mut self.folder_sync {
+            sync.add_record(&record);
+        }
+        history::append(record);
+    }
+
+    fn finish_reminder(&mut self, acknowledged: bool) {
+        self.reminder_until = None;
+        self.record(Entry::Reminder { acknowledged });
+    }
+
+    fn check_time(&mut self) {
+        // the host's next phase finishes it when it comes, so it isn't finished twice
+        let following = self.team.as_ref().is_some_and(|t| t.is_following());
+        if self.timer.is_done() && !following {
+            self.next_phase(false);
+        }
+        if self.countdown.is_done() {
+            self.play_completed_sound(None);
+            let length = self.countdown.get_duration().as_secs();
+            self.record(Entry::Countdown { length });
+            self.notifications
+                .push(Notification::new("Countdown Done!".to_string()));
+            self.announcement = "Countdown done".to_string();
+            self.countdown.reset();
+        }
+        for timer in &mut self.timers {
+            if !timer.timer.is_done() {
+                continue;
+            }
+            if self.config_manager.get_sound() {
+                Self::play_sound(timer.config.sound.clone());
+            }
+            let text = format!("{} Done!", timer.timer.get_name());
+            self.notifications.push(Notification::new(text.clone()));
+            self.announcement = text;
+            // ready to be used again
+            timer.timer.reset();
+        }
+    }
+
+    /// Keeps a record of it if it was used, and starts again with the lap length from the config
+    fn reset_stopwatch(&mut self) {
+        let elapsed = self.stopwatch.time_elapsed();
+        if !elapsed.is_zero() {
+            let laps = self.stopwatch.get_laps().iter().map(|l| l.as_secs());
+            self.record(Entry::Stopwatch {
+                elapsed: elapsed.as_secs(),
+                laps: laps.collect(),
+            });
+        }
+        self.stopwatch.reset(self.config_manager.get_lap_length());
+    }
+
+    /// Plays the file, or the normal sound if there isn't one
+    fn play_sound(file: Option<PathBuf>) {
+        match file {
+            Some(file) => play_sound_file(file),
+            None => play_sound(RAW_COMPLETE_SOUND),
+        }
+    }
+
+    fn play_completed_sound(&self, file: Option<PathBuf>) {
+        if self.config_manager.get_sound() {
+            Self::play_sound(file);
+        }
+    }
+
+    fn next_phase(&mut self, skipped: bool) {
+        // the step is gone once the timer moves on
+        let name = RingTimer::get_name(&self.timer).to_string();
+        let sound = self.timer.get_step().and_then(|(_, s)| s.sound.clone());
+        self.play_completed_sound(sound);
+        let finished = self
+            .timer
+            .next_phase(&self.config_manager.config.lock().unwrap());
+        if !skipped {
+            let schedule = self.config_manager.get_schedule();
+            let text = if schedule.enabled
+                && matches!(finished, PomodoroPhase::Break { .. })
+                && !schedule.is_working_hours(utils::local_time())
+            {
+                // there's no more work to be done today
+                format!("{} Done! Working hours are over", name)
+            } else {
+                format!("{} Done!", name)
+            };
+            self.notifications.push(Notification::new(text));
+        }
+        let elapsed = finished.time_elapsed(Instant::now()).unwrap_or_default();
+        let length = finished.get_duration().map_or(elapsed, |d| elapsed.min(d));
+        self.record(Entry::Pomodoro {
+            phase: finished.get_name().to_string(),
+            length: length.as_secs(),
+            skipped,
+        });
+        #[cfg(target_os = "linux")]
+        if !skipped && matches!(finished, PomodoroPhase::Work { .. }) {
+            self.completed_today.1 += 1;
+        }
+        self.announcement = format!("{} started", RingTimer::get_name(&self.timer));
+        self.postponed_until = None;
+        self.postpones_used = 0;
+        self.reminders_given = 0;
+    }
+
+    fn run_hooks(&mut self) {
+        let events = self.timer.take_events();
+        if !events.is_empty() {
+            hooks::run_hooks(events, &self.config_manager.config.lock().unwrap());
+        }
+    }
+
+    fn get_percent(&self, theme: &Theme) -> Percent {
+        let percent = if theme.reduce_motion {
+            self.timer.to_stepped_percent()
+        } else {
+            self.timer.to_percent()
+        };
+        percent.unwrap_or(Percent::new(100.0).expect("Should be valid"))
+    }
+
+    /// The timer in the main ring, which depends on the mode
+    fn get_ring(&mut self, theme: &Theme) -> (Percent, &mut dyn RingTimer) {
+        match self.config_manager.get_mode() {
+            Mode::Pomodoro | Mode::Flowtime => (self.get_percent(theme), &mut self.timer),
+            Mode::Countdown => (
+                self.countdown
+                    .to_percent()
+                    .unwrap_or(Percent::new(100.0).expect("Should be valid")),
+                &mut self.countdown,
+            ),
+            Mode::Stopwatch => (self.stopwatch.to_percent(), &mut self.stopwatch),
+        }
+    }
+
+    fn get_repaint_time(theme: &Theme) -> Duration {
+        if theme.reduce_motion {
+            Duration::from_secs(1)
+        } else {
+            Duration::from_millis(5)
+        }
+    }
+
+    fn get_full_viewport(config_manager: &ConfigManager) -> ViewportBuilder {
+        viewport::full_viewport(
+            config_manager.get_window_size().map(Vec2::from),
+            config_manager
+                .get_window_position()
+                .map(|[x, y]| Pos2::new(x, y)),
+        )
+    }
+
+    fn toggle_compact(&mut self, ctx: &egui::Context) {
+        self.compact = !self.compact;
+        let target = if self.compact {
+            let position = self
+                .config_manager
+                .get_compact_position()
+                .map(|[x, y]| Pos2::new(x, y));
+            viewport::compact_viewport(position)
+        } else {
+            // the position the compact window was left at
+            self.config_manager.save();
+            let full = Self::get_full_viewport(&self.config_manager);
+            viewport::uncompact_viewport(full.inner_size, full.position)
+        };
+        viewport::switch_viewport(ctx, &mut self.viewport, target);
+    }
+
+    fn draw_full(&mut self, ctx: &egui::Context, theme: &Theme) {
+        let layout = WindowLayout::from_size(ctx.screen_rect().size());
+        if layout == WindowLayout::Landscape {
+            egui::SidePanel::right("controls")
+                .resizable(false)
+                .exact_width(ctx.screen_rect().width() / 2.0)
+                .show(ctx, |ui| self.draw_controls(ui, theme));
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             // check if time is done
             self.check_time();
+            ui::draw_announcement(ui, &self.announcement);
+
+            // header
+            if layout != WindowLayout::Tiny {
+                ui::draw_header(ui);
+            }
 
-            // key inputs
-            ui.input_mut(|i| {
-                if i.consume_key(egui::Modifiers::NONE, egui::Key::Space) {
-                    self.phase.toggle();
+            let (percent, ring) = self.get_ring(theme);
+            let circle = ProgressCircle::new(percent, ring, theme);
+            match layout {
+                WindowLayout::Portrait => {
+                    ui.add(circle);
+                    self.draw_controls(ui, theme);
                 }
-            });
+                WindowLayout::Landscape => {
+                    ui.add(circle.fill(true));
+                }
+                WindowLayout::Tiny => {
+                    ui.add(circle.compact(true));
+                }
+            }
+        });
 
-            // header
-            ui::draw_header(ui);
-
-            let percent = self
-                .phase
-                .to_percent()
-                .unwrap_or(Percent::new(100.0).expect("Should be valid"));
-            ui.add(ProgressCircle::new(percent, &mut self.phase));
-
-            let status = *self.config_manager.status.lock().unwrap();
-            if let Action::NextPhase = ui::draw_stats_bar(ui, &self.stats, status) {
-                self.next_phase(true);
-            }
-
-            let config_actions = ui::draw_config(
-                ui,
-                &self.config_manager.get_work_time(),
-                &self.config_manager.get_break_time(),
-            );
-            for action in &config_actions {
-                match action {
-                    Action::ModifyWorkPhaseConfig(d) => self.config_manager.set_work_time(*d),
-                    Action::ModifyBreakPhaseConfig(d) => self.config_manager.set_break_time(*d),
-                    _ => {}
+        // keeps track of the size and position, so the window opens the same way next time
+        if let (Some(inner), Some(outer)) = ctx.input(|i| {
+            let viewport = i.viewport();
+            (viewport.inner_rect, viewport.outer_rect)
+        }) {
+            let (size, position) = ([inner.width(), inner.height()], [outer.min.x, outer.min.y]);
+            if self.config_manager.get_window_size() != Some(size)
+                || self.config_manager.get_window_position() != Some(position)
+            {
+                self.config_manager.set_window_geometry(size, position);
+            }
+        }
+    }
+
+    fn draw_controls(&mut self, ui: &mut egui::Ui, theme: &Theme) {
+        if let Some(reminder) = self.notifications.iter().find(|n| n.countdown) {
+            let action = ui::draw_reminder(ui, &reminder.get_text());
+            self.handle_action(ui.ctx(), action);
+        }
+        let status = self.config_manager.status.lock().unwrap().clone();
+        let mode = self.config_manager.get_mode();
+        let sequence = self.config_manager.get_sequence();
+        let step = self.timer.get_step().map(|(i, _)| i);
+        let action = ui::draw_stats_bar(
+            ui,
+            &self.timer,
+            mode,
+            step.map(|i| (i, sequence.len())),
+            self.team.as_ref().map(team::Session::get_peers).as_deref(),
+            self.stopwatch.get_laps().len(),
+            status,
+        );
+        self.handle_action(ui.ctx(), action);
+        if mode == Mode::Stopwatch {
+            ui::draw_laps(ui, self.stopwatch.get_laps());
+        }
+        if let Some(step) = step {
+            let action = ui::draw_sequence(ui, &sequence, step);
+            self.handle_action(ui.ctx(), action);
+        }
+        let schedule = self.config_manager.get_schedule();
+        if schedule.enabled {
+            let phase_left = match mode {
+                Mode::Pomodoro | Mode::Flowtime if !self.timer.get_phase().is_open_ended() => {
+                    Some(self.timer.get_time_left())
                 }
+                _ => None,
+            };
+            ui::draw_schedule(ui, &schedule, utils::local_time(), phase_left);
+        }
+
+        let timer_actions = timers::draw_timers(ui, &mut self.timers, theme);
+        for action in timer_actions {
+            self.handle_action(ui.ctx(), action);
+        }
+
+        let config_actions = ui::draw_config(ui, &self.config_manager.config.lock().unwrap());
+        let changed = !config_actions.is_empty();
+        for action in config_actions {
+            self.handle_action(ui.ctx(), action);
+        }
+        if changed {
+            self.config_manager.save();
+        }
+    }
+
+    fn draw_compact(&mut self, ctx: &egui::Context) {
+        let theme = self.config_manager.get_theme();
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none().inner_margin(5.0))
+            .show(ctx, |ui| {
+                self.check_time();
+                ui::draw_announcement(ui, &self.announcement);
+
+                let (percent, ring) = self.get_ring(&theme);
+                let response = ui.add(ProgressCircle::new(percent, ring, &theme).compact(true));
+                if response.drag_started() {
+                    ui.ctx().send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                }
+                if response.double_clicked() {
+                    self.toggle_compact(ui.ctx());
+                }
+            });
+
+        // keeps track of where the window is dragged to, so it opens there next time
+        if let Some(rect) = ctx.input(|i| i.viewport().outer_rect) {
+            let position = [rect.min.x, rect.min.y];
+            if self.config_manager.get_compact_position() != Some(position) {
+                self.config_manager.set_compact_position(position);
             }
-            if !config_actions.is_empty() {
-                self.config_manager.save();
+        }
+    }
+}
+
+impl eframe::App for App {
+    // This will get called every time the app updates, or every 5ms, which ever is faster
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let theme = self.config_manager.get_theme();
+        theme::apply_visuals(ctx, &theme, frame.info().system_theme);
+
+        #[cfg(target_os = "linux")]
+        self.update_tray(ctx);
+        #[cfg(target_os = "linux")]
+        self.update_dnd();
+        self.update_instance(ctx, &theme);
+        self.update_focus_guard();
+        self.update_break_screen(ctx);
+        self.update_reminders();
+        self.update_schedule();
+        self.update_team();
+        self.update_api(ctx, &theme);
+        self.update_mqtt(ctx, &theme);
+        self.update_folder_sync();
+        #[cfg(target_os = "linux")]
+        self.update_dbus(ctx, &theme);
+
+        // key inputs
+        let number_keys = [
+            egui::Key::Num1,
+            egui::Key::Num2,
+            egui::Key::Num3,
+            egui::Key::Num4,
+            egui::Key::Num5,
+            egui::Key::Num6,
+            egui::Key::Num7,
+            egui::Key::Num8,
+            egui::Key::Num9,
+        ];
+        let mut toggled = vec![];
+        // so typing a number into a text box doesn't start a timer
+        let typing = ctx.wants_keyboard_input();
+        ctx.input_mut(|i| {
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::Space) {
+                self.get_ring(&theme).1.toggle();
+            }
+            for (n, key) in number_keys.into_iter().enumerate() {
+                if !typing && i.consume_key(egui::Modifiers::NONE, key) {
+                    toggled.push(Action::ToggleTimer(n));
+                }
             }
         });
+        for action in toggled {
+            self.handle_action(ctx, action);
+        }
+
+        if self.compact {
+            self.draw_compact(ctx);
+            self.run_hooks();
+            draw_notification(ctx, &mut self.notifications, &self.screen_size);
+            ctx.request_repaint_after(Self::get_repaint_time(&theme));
+            return;
+        }
+
+        self.draw_full(ctx, &theme);
+        self.run_hooks();
 
         draw_notification(ctx, &mut self.notifications, &self.screen_size);
 
         // this is what sets the slowest update speed
-        ctx.request_repaint_after(Duration::from_millis(5));
+        ctx.request_repaint_after(Self::get_repaint_time(&theme));
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // the window geometry isn't saved while it's changing
+        self.config_manager.save_blocking();
+        // puts everything back if it's quit mid work phase
+        #[cfg(target_os = "linux")]
+        self.dnd.take();
+        self.focus_guard.stop();
     }
 
     fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
@@ -151,23 +920,96 @@ impl eframe::App for App {
     }
 }
 
+/// A window with only the error in it, for when the app can't start
+fn show_error(message: String) {
+    println!("{message}");
+    let native_options = eframe::NativeOptions {
+        viewport: ViewportBuilder::default()
+            .with_inner_size([360.0, 140.0])
+            .with_icon(utils::load_icon()),
+        ..Default::default()
+    };
+    let _ = eframe::run_simple_native("Pomodoro Timer", native_options, move |ctx, _| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Pomodoro can't start");
+            ui.label(&message);
+            if ui.button("Close").clicked() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        });
+    });
+}
+
 fn main() {
-    fn build_viewport() -> ViewportBuilder {
-        ViewportBuilder::default()
-            .with_resizable(false)
-            .with_max_inner_size(Vec2::new(350.0, 450.0))
-            .with_icon(utils::load_icon())
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            println!("{e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if let Some(subcommand) = args.subcommand {
+        std::process::exit(cli::run(subcommand, args.config));
+    }
+    let config_file = match paths::get_config_file(args.config.clone()) {
+        Ok(config_file) => config_file,
+        Err(e) => {
+            show_error(e);
+            std::process::exit(1);
+        }
+    };
+    // only one instance writes each config, later launches pass their arguments on to it
+    let lock = match instance::launch(&config_file, &args.get_forwarded()) {
+        instance::Launch::First(lock) => lock,
+        instance::Launch::Forwarded => {
+            if args.has_launch_options() {
+                println!(
+                    "Already running, so only --profile, --start, --toggle and --skip were passed on"
+                );
+            }
+            return;
+        }
+        instance::Launch::NotAnswering => {
+            println!("Already running, but it isn't answering");
+            std::process::exit(1);
+        }
+    };
+
+    let mut config_manager = match ConfigManager::new(Some(config_file)) {
+        Ok(config_manager) => config_manager,
+        Err(e) => {
+            show_error(e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = config_manager.load_blocking() {
+        show_error(e);
+        std::process::exit(1);
+    }
+    if let Err(e) = config_manager.apply_overrides(&args.overrides, args.save) {
+        println!("{e}");
+        std::process::exit(2);
+    }
+    let mut startup: Vec<Action> = args
+        .commands
+        .into_iter()
+        .filter_map(instance::to_action)
+        .collect();
+    if args.compact {
+        startup.push(Action::ToggleCompact);
     }
 
     let native_options = eframe::NativeOptions {
-        centered: true,
-        viewport: build_viewport(),
-        default_theme: Theme::Dark,
+        centered: config_manager.get_window_position().is_none(),
+        viewport: App::get_full_viewport(&config_manager).with_icon(utils::load_icon()),
+        default_theme: eframe::Theme::Dark,
+        // so the system theme is known, the one from the config is put back on top of it
+        follow_system_theme: true,
         ..Default::default()
     };
     let _ = eframe::run_native(
         "Pomodoro Timer",
         native_options,
-        Box::new(|cc| Box::new(App::new(cc))),
+        Box::new(|cc| Box::new(App::new(cc, config_manager, lock, startup))),
     );
 }
@@ -0,0 +1,203 @@
+use std::{
+    io::{BufReader, Write},
+    net::{Shutdown, TcpStream},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui;
+use pomodoro_core::{
+    api::Status,
+    config::Mqtt as MqttConfig,
+    mqtt::{self, Packet},
+};
+
+use crate::ui::Action;
+
+/// How often the state gets published when nothing changed
+const REPUBLISH_INTERVAL: Duration = Duration::from_secs(60);
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A retained message for each topic
+type Messages = Vec<(String, String)>;
+
+/// Publishes the timer's state to a broker, and turns what comes in on the command topic into
+/// actions
+pub struct Mqtt {
+    topic: String,
+    outgoing: Sender<Messages>,
+    actions: Receiver<Action>,
+    last: Option<Status>,
+    last_sent: Option<Instant>,
+}
+
+impl Mqtt {
+    pub fn new(ctx: &egui::Context, config: &MqttConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let (outgoing, messages) = mpsc::channel();
+        let (sender, actions) = mpsc::channel();
+        let topic = config.topic.clone();
+        let config = config.clone();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let mut last = vec![];
+            loop {
+                match TcpStream::connect((config.host.as_str(), config.port)) {
+                    Ok(stream) => {
+                        if run(stream, &config, &messages, &mut last, &sender, &ctx) {
+                            break;
+                        }
+                    }
+                    Err(e) => println!("Could not connect to {}: {e}", config.host),
+                }
+                thread::sleep(RECONNECT_INTERVAL);
+            }
+        });
+        Some(Self {
+            topic,
+            outgoing,
+            actions,
+            last: None,
+            last_sent: None,
+        })
+    }
+
+    /// Gets called every frame, only publishing when something changed or once a minute
+    pub fn update(&mut self, status: Status) {
+        let changed = self.last.as_ref().is_none_or(|l| status.differs_from(l));
+        if !changed
+            && self
+                .last_sent
+                .is_some_and(|t| t.elapsed() < REPUBLISH_INTERVAL)
+        {
+            return;
+        }
+        let messages = vec![
+            (format!("{}/phase", self.topic), status.name.clone()),
+            (
+                format!("{}/remaining", self.topic),
+                status.time.as_secs().to_string(),
+            ),
+            (format!("{}/paused", self.topic), status.paused.to_string()),
+        ];
+        let _ = self.outgoing.send(messages);
+        self.last = Some(status);
+        self.last_sent = Some(Instant::now());
+    }
+
+    pub fn get_actions(&self) -> Vec<Action> {
+        self.actions.try_iter().collect()
+    }
+}
+
+/// Talks to the broker until the connection drops, or returns true once the app has closed
+fn run(
+    stream: TcpStream,
+    config: &MqttConfig,
+    messages: &Receiver<Messages>,
+    last: &mut Messages,
+    actions: &Sender<Action>,
+    ctx: &egui::Context,
+) -> bool {
+    let Ok(mut writer) = stream.try_clone() else {
+        return false;
+    };
+    let connect = mqtt::connect(
+        &config.client_id,
+        config.username.as_deref(),
+        config.password.as_deref(),
+        KEEP_ALIVE.as_secs() as u16,
+    );
+    let connect = match connect {
+        Ok(connect) => connect,
+        Err(e) => {
+            println!("Could not connect to {}: {e}", config.host);
+            return false;
+        }
+    };
+    if writer.write_all(&connect).is_err() {
+        return false;
+    }
+    let mut reader = BufReader::new(stream);
+    match mqtt::read_packet(&mut reader) {
+        Ok(Packet::ConnAck { code: 0 }) => {}
+        Ok(Packet::ConnAck { code }) => {
+            println!("{} refused the connection with code {code}", config.host);
+            return false;
+        }
+        _ => return false,
+    }
+    let command_topic = format!("{}/command", config.topic);
+    let subscribe = match mqtt::subscribe(1, &command_topic) {
+        Ok(subscribe) => subscribe,
+        Err(e) => {
+            println!("Could not subscribe to {command_topic}: {e}");
+            return false;
+        }
+    };
+    if writer.write_all(&subscribe).is_err() {
+        return false;
+    }
+
+    let actions = actions.clone();
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        while let Ok(packet) = mqtt::read_packet(&mut reader) {
+            let Packet::Publish { topic, payload } = packet else {
+                continue;
+            };
+            if topic != command_topic {
+                continue;
+            }
+            let action = match String::from_utf8_lossy(&payload).trim() {
+                "toggle" => Action::TogglePhase,
+                "skip" => Action::NextPhase,
+                _ => continue,
+            };
+            let _ = actions.send(action);
+            ctx.request_repaint();
+        }
+    });
+
+    // what was published before is sent again, in case it changed while not connected
+    let mut pending = Some(std::mem::take(last));
+    loop {
+        let batch = match pending.take() {
+            Some(batch) => batch,
+            None => match messages.recv_timeout(KEEP_ALIVE / 2) {
+                Ok(batch) => batch,
+                Err(RecvTimeoutError::Timeout) => {
+                    if writer.write_all(&mqtt::ping()).is_err() {
+                        return false;
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    let _ = writer.write_all(&mqtt::disconnect());
+                    let _ = writer.shutdown(Shutdown::Both);
+                    return true;
+                }
+            },
+        };
+        for (topic, payload) in &batch {
+            let publish = match mqtt::publish(topic, payload.as_bytes(), true) {
+                Ok(publish) => publish,
+                Err(e) => {
+                    println!("Could not publish to {topic}: {e}");
+                    continue;
+                }
+            };
+            if writer.write_all(&publish).is_err() {
+                *last = batch;
+                // stops the reading thread too
+                let _ = writer.shutdown(Shutdown::Both);
+                return false;
+            }
+        }
+        *last = batch;
+    }
+}
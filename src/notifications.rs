@@ -3,7 +3,7 @@ use std::time::{Duration, Instant};
 use eframe::{
     egui::{self, Sense, ViewportBuilder},
     emath::Align2,
-    epaint::{vec2, Color32, FontFamily, FontId, Pos2, Stroke},
+    epaint::{vec2, FontFamily, FontId, Pos2, Stroke},
 };
 
 use crate::utils::MonitorSize;
@@ -14,6 +14,8 @@ pub struct Notification {
     pub time_start: Instant,
     pub duration: Duration,
     pub text: String,
+    /// Shows how many seconds are left after the text
+    pub countdown: bool,
 }
 
 impl Notification {
@@ -22,6 +24,25 @@ impl Notification {
             time_start: Instant::now(),
             duration: NOTIFICATION_DURATION,
             text,
+            countdown: false,
+        }
+    }
+
+    pub fn with_countdown(text: String, duration: Duration) -> Self {
+        Self {
+            time_start: Instant::now(),
+            duration,
+            text,
+            countdown: true,
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        if self.countdown {
+            let left = self.duration.saturating_sub(self.time_start.elapsed());
+            format!("{} ({}s)", self.text, left.as_secs() + 1)
+        } else {
+            self.text.clone()
         }
     }
 }
@@ -52,9 +73,12 @@ fn draw_inner_notification(
         ui.painter().text(
             rect.left_center(),
             Align2::LEFT_CENTER,
-            &notifications.get(i - *removed).expect("Should exist").text,
+            notifications
+                .get(i - *removed)
+                .expect("Should exist")
+                .get_text(),
             FontId::new(20.0, FontFamily::default()),
-            Color32::WHITE,
+            ui.visuals().text_color(),
         );
 
         if sense.clicked() {
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use directories::BaseDirs;
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// The folder the app is in, if there's a config file next to it, so everything can be kept on
+/// a USB stick or in a project folder
+fn get_portable_dir() -> Option<PathBuf> {
+    let dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    dir.join(CONFIG_FILE).is_file().then_some(dir)
+}
+
+/// `--config` first, then `POMODORO_CONFIG`, then the portable one, then the usual place
+pub fn get_config_file(arg: Option<PathBuf>) -> Result<PathBuf, String> {
+    if let Some(file) = arg {
+        return Ok(file);
+    }
+    if let Some(file) = std::env::var_os("POMODORO_CONFIG").filter(|f| !f.is_empty()) {
+        return Ok(PathBuf::from(file));
+    }
+    if let Some(dir) = get_portable_dir() {
+        return Ok(dir.join(CONFIG_FILE));
+    }
+    BaseDirs::new()
+        .map(|d| d.config_dir().join("Pomodoro").join(CONFIG_FILE))
+        .ok_or(
+            "Could not find a home directory to keep the config in. Set POMODORO_CONFIG to \
+             where it should go, or put a config.toml next to the app."
+                .to_string(),
+        )
+}
+
+/// Where the history is kept
+pub fn get_data_dir() -> Option<PathBuf> {
+    get_portable_dir().or_else(|| BaseDirs::new().map(|d| d.data_dir().join("Pomodoro")))
+}
+
+/// Where what only matters while the app is running is kept, like the lock file
+pub fn get_state_dir() -> Option<PathBuf> {
+    get_portable_dir().or_else(|| {
+        // only linux has a state directory
+        BaseDirs::new().map(|d| d.state_dir().unwrap_or(d.data_local_dir()).join("Pomodoro"))
+    })
+}
+
+/// Where things were kept before they were split up by what they are
+pub fn get_old_dir() -> Option<PathBuf> {
+    BaseDirs::new().map(|d| d.config_dir().join("Pomodoro"))
+}
@@ -0,0 +1,299 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use pomodoro_core::{
+    config::{TeamSync, TeamSyncMode},
+    team::{clock_offset, unix_millis, Message, PhaseState},
+    PomodoroPhase, Timer,
+};
+
+/// How often the host sends its phase when nothing changed, so clients that drift get pulled back
+const STATE_INTERVAL: Duration = Duration::from_secs(1);
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Either side of a team sync session
+pub enum Session {
+    Host(Host),
+    Client(Client),
+}
+
+impl Session {
+    /// Hosts or joins a session, depending on the config
+    pub fn new(config: &TeamSync) -> Option<Self> {
+        let name = if config.name.is_empty() {
+            std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_else(|_| "someone".to_string())
+        } else {
+            config.name.clone()
+        };
+        match config.mode? {
+            TeamSyncMode::Host => match Host::new(&config.address, name) {
+                Ok(host) => Some(Self::Host(host)),
+                Err(e) => {
+                    println!("Could not host team sync on {}: {e}", config.address);
+                    None
+                }
+            },
+            TeamSyncMode::Join => Some(Self::Client(Client::new(config.address.clone(), name))),
+        }
+    }
+
+    /// If this is a client that has the host's phase, so the host is what moves it on
+    pub fn is_following(&self) -> bool {
+        match self {
+            Self::Host(_) => false,
+            Self::Client(client) => client.following,
+        }
+    }
+
+    /// Everyone in the session, which is empty while a client isn't connected
+    pub fn get_peers(&self) -> Vec<String> {
+        match self {
+            Self::Host(host) => host.get_peers(),
+            Self::Client(client) => client.peers.clone(),
+        }
+    }
+}
+
+/// A client connected to the host
+struct Peer {
+    id: usize,
+    name: String,
+    /// Lines to write to it
+    sender: Sender<String>,
+}
+
+#[derive(Default)]
+struct Peers {
+    next_id: usize,
+    list: Vec<Peer>,
+}
+
+impl Peers {
+    /// Sends a line to every client, forgetting the ones that have gone
+    fn broadcast(&mut self, message: &Message) {
+        let line = message.to_string();
+        self.list
+            .retain(|peer| peer.sender.send(line.clone()).is_ok());
+    }
+
+    fn names(&self, host: &str) -> Vec<String> {
+        std::iter::once(host.to_string())
+            .chain(self.list.iter().map(|peer| peer.name.clone()))
+            .collect()
+    }
+}
+
+pub struct Host {
+    name: String,
+    peers: Arc<Mutex<Peers>>,
+    last_state: Option<PhaseState>,
+    last_sent: Option<Instant>,
+}
+
+impl Host {
+    fn new(address: &str, name: String) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let peers = Arc::new(Mutex::new(Peers::default()));
+        let host = Self {
+            name: name.clone(),
+            peers: peers.clone(),
+            last_state: None,
+            last_sent: None,
+        };
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let peers = peers.clone();
+                let name = name.clone();
+                thread::spawn(move || serve(stream, &peers, &name));
+            }
+        });
+        Ok(host)
+    }
+
+    /// Sends the timer's phase to everyone when it changes, and every so often otherwise
+    pub fn update(&mut self, timer: &Timer) {
+        let state = PhaseState::new(timer.get_phase(), Instant::now());
+        let changed = self.last_state.is_none_or(|last| last.differs_from(&state));
+        if !changed && self.last_sent.is_some_and(|t| t.elapsed() < STATE_INTERVAL) {
+            return;
+        }
+        self.last_state = Some(state);
+        self.last_sent = Some(Instant::now());
+        let at = unix_millis();
+        self.peers
+            .lock()
+            .unwrap()
+            .broadcast(&Message::State { state, at });
+    }
+
+    fn get_peers(&self) -> Vec<String> {
+        self.peers.lock().unwrap().names(&self.name)
+    }
+}
+
+/// Talks to one client until it goes away
+fn serve(stream: TcpStream, peers: &Mutex<Peers>, host: &str) {
+    let Ok(writer) = stream.try_clone() else {
+        return;
+    };
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || write_lines(writer, receiver));
+    let mut id = None;
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        match Message::parse(&line) {
+            Some(Message::Hello(name)) if id.is_none() => {
+                let mut peers = peers.lock().unwrap();
+                id = Some(peers.next_id);
+                let peer = Peer {
+                    id: peers.next_id,
+                    name,
+                    sender: sender.clone(),
+                };
+                peers.next_id += 1;
+                peers.list.push(peer);
+                let names = peers.names(host);
+                peers.broadcast(&Message::Peers(names));
+            }
+            Some(Message::Ping(sent)) => {
+                let pong = Message::Pong {
+                    sent,
+                    host: unix_millis(),
+                };
+                let _ = sender.send(pong.to_string());
+            }
+            _ => {}
+        }
+    }
+    if let Some(id) = id {
+        let mut peers = peers.lock().unwrap();
+        peers.list.retain(|peer| peer.id != id);
+        let names = peers.names(host);
+        peers.broadcast(&Message::Peers(names));
+    }
+}
+
+/// Writes lines until there are no senders left or the connection drops
+fn write_lines(mut stream: TcpStream, receiver: Receiver<String>) {
+    for line in receiver {
+        if writeln!(stream, "{line}").is_err() {
+            break;
+        }
+    }
+    // stops the reading side too
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+enum ClientEvent {
+    /// A message from the host, with when it arrived
+    Message(Message, u64),
+    Disconnected,
+}
+
+/// A phase from the host to take on
+pub struct Mirror {
+    pub phase: PomodoroPhase,
+    /// If it carries on from the host's last phase, rather than being the first since connecting
+    pub following: bool,
+}
+
+pub struct Client {
+    receiver: Receiver<ClientEvent>,
+    /// How far the host's clock is ahead of this one, in milliseconds
+    offset: i64,
+    following: bool,
+    peers: Vec<String>,
+}
+
+impl Client {
+    fn new(address: String, name: String) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || loop {
+            if let Ok(stream) = TcpStream::connect(&address) {
+                follow(stream, &name, &sender);
+            }
+            // the app has closed
+            if sender.send(ClientEvent::Disconnected).is_err() {
+                break;
+            }
+            thread::sleep(RECONNECT_INTERVAL);
+        });
+        Self {
+            receiver,
+            offset: 0,
+            following: false,
+            peers: vec![],
+        }
+    }
+
+    /// The host's latest phase, if it sent one since last time
+    pub fn update(&mut self) -> Option<Mirror> {
+        let mut latest = None;
+        for event in self.receiver.try_iter() {
+            match event {
+                ClientEvent::Message(Message::Pong { sent, host }, received) => {
+                    self.offset = clock_offset(sent, host, received);
+                }
+                ClientEvent::Message(Message::Peers(peers), _) => self.peers = peers,
+                ClientEvent::Message(Message::State { state, at }, _) => latest = Some((state, at)),
+                ClientEvent::Message(..) => {}
+                ClientEvent::Disconnected => {
+                    self.following = false;
+                    self.peers.clear();
+                }
+            }
+        }
+        let (state, at) = latest?;
+        // how long ago the host sent it, going by the host's clock
+        let delay = (unix_millis() as i64 + self.offset - at as i64).max(0) as u64;
+        let mirror = Mirror {
+            phase: state.to_phase(Instant::now(), Duration::from_millis(delay)),
+            following: self.following,
+        };
+        self.following = true;
+        Some(mirror)
+    }
+}
+
+/// Passes on what the host sends until the connection drops, pinging it every so often
+fn follow(stream: TcpStream, name: &str, sender: &Sender<ClientEvent>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    if writeln!(writer, "{}", Message::Hello(name.to_string())).is_err() {
+        return;
+    }
+    thread::spawn(move || {
+        while writeln!(writer, "{}", Message::Ping(unix_millis())).is_ok() {
+            thread::sleep(PING_INTERVAL);
+        }
+    });
+    for line in BufReader::new(&stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let Some(message) = Message::parse(&line) else {
+            continue;
+        };
+        if sender
+            .send(ClientEvent::Message(message, unix_millis()))
+            .is_err()
+        {
+            break;
+        }
+    }
+    let _ = stream.shutdown(Shutdown::Both);
+}
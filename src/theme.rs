@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use eframe::{
+    egui::{self, FontData, FontDefinitions, FontFamily, Stroke, Visuals},
+    epaint::Color32,
+};
+
+use pomodoro_core::{
+    config::{Theme, ThemeMode},
+    Timer,
+};
+
+use crate::circle_widget::RingTimer;
+
+/// The colors in the theme are stored as hex, these turn them into something that can be drawn
+pub trait ThemeColors {
+    fn get_work_color(&self) -> Color32;
+    fn get_break_color(&self) -> Color32;
+    fn get_background_color(&self) -> Option<Color32>;
+    fn get_text_color(&self) -> Option<Color32>;
+}
+
+fn parse_color(hex: &str, fallback: &str) -> Color32 {
+    Color32::from_hex(hex).unwrap_or_else(|_| Color32::from_hex(fallback).unwrap())
+}
+
+impl ThemeColors for Theme {
+    fn get_work_color(&self) -> Color32 {
+        if self.mode == ThemeMode::HighContrast {
+            return Color32::GREEN;
+        }
+        parse_color(&self.work_color, "#3aeb34")
+    }
+    fn get_break_color(&self) -> Color32 {
+        if self.mode == ThemeMode::HighContrast {
+            return Color32::YELLOW;
+        }
+        parse_color(&self.break_color, "#dceb34")
+    }
+    fn get_background_color(&self) -> Option<Color32> {
+        self.background_color
+            .as_ref()
+            .and_then(|c| Color32::from_hex(c).ok())
+    }
+    fn get_text_color(&self) -> Option<Color32> {
+        self.text_color
+            .as_ref()
+            .and_then(|c| Color32::from_hex(c).ok())
+    }
+}
+
+/// The color of the timer's phase, grayed out if it's paused
+pub fn phase_color(visuals: &Visuals, timer: &Timer, theme: &Theme) -> Color32 {
+    let color = RingTimer::get_color(timer, theme);
+    if timer.is_paused() {
+        visuals.gray_out(color)
+    } else {
+        color
+    }
+}
+
+/// Sets the visuals from the theme, only touching egui's style when something changed
+pub fn apply_visuals(ctx: &egui::Context, theme: &Theme, system_theme: Option<eframe::Theme>) {
+    let visuals = match theme.mode {
+        ThemeMode::HighContrast => high_contrast_visuals(),
+        mode => {
+            let dark = match mode {
+                ThemeMode::Light => false,
+                ThemeMode::System => system_theme != Some(eframe::Theme::Light),
+                _ => true,
+            };
+            let mut visuals = if dark {
+                Visuals::dark()
+            } else {
+                Visuals::light()
+            };
+            if let Some(background) = theme.get_background_color() {
+                visuals.panel_fill = background;
+                visuals.window_fill = background;
+            }
+            visuals.override_text_color = theme.get_text_color();
+            visuals
+        }
+    };
+
+    if ctx.style().visuals != visuals {
+        ctx.set_visuals(visuals);
+    }
+}
+
+fn high_contrast_visuals() -> Visuals {
+    let mut visuals = Visuals::dark();
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::BLACK;
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+    for widget in [
+        &mut visuals.widgets.noninteractive,
+        &mut visuals.widgets.inactive,
+        &mut visuals.widgets.hovered,
+        &mut visuals.widgets.active,
+        &mut visuals.widgets.open,
+    ] {
+        widget.bg_stroke = Stroke::new(2.0, Color32::WHITE);
+        widget.fg_stroke.color = Color32::WHITE;
+    }
+    visuals.selection.bg_fill = Color32::YELLOW;
+    visuals.selection.stroke = Stroke::new(2.0, Color32::BLACK);
+    visuals
+}
+
+/// Puts the font in front of the default ones, so they are only used for missing glyphs
+pub fn load_font(ctx: &egui::Context, path: &Path) {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) => {
+            println!("Could not load font {}: {e}", path.display());
+            return;
+        }
+    };
+    let mut fonts = FontDefinitions::default();
+    fonts
+        .font_data
+        .insert("custom".to_owned(), FontData::from_owned(data));
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts
+            .families
+            .entry(family)
+            .or_default()
+            .insert(0, "custom".to_owned());
+    }
+    ctx.set_fonts(fonts);
+}
@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use eframe::{
+    egui::{DragValue, Ui},
+    epaint::{vec2, Color32, Vec2},
+};
+
+use pomodoro_core::{
+    config::{Theme, TimerConfig},
+    NamedTimer, Percent,
+};
+
+use crate::{
+    circle_widget::{ProgressCircle, RingTimer},
+    theme::ThemeColors,
+    ui::Action,
+};
+
+const RING_SIZE: Vec2 = vec2(70.0, 70.0);
+
+/// One of the timers that run separately from the pomodoro one
+pub struct ExtraTimer {
+    pub config: TimerConfig,
+    pub timer: NamedTimer,
+}
+
+impl ExtraTimer {
+    pub fn new(config: TimerConfig) -> Self {
+        let timer = NamedTimer::new(&config.name, config.length);
+        Self { config, timer }
+    }
+}
+
+impl RingTimer for ExtraTimer {
+    fn get_name(&self) -> &str {
+        self.timer.get_name()
+    }
+    fn get_time(&self) -> Duration {
+        self.timer.get_time_left()
+    }
+    fn is_paused(&self) -> bool {
+        self.timer.is_paused()
+    }
+    fn toggle(&mut self) {
+        self.timer.toggle()
+    }
+    fn get_color(&self, theme: &Theme) -> Color32 {
+        self.config
+            .color
+            .as_ref()
+            .and_then(|c| Color32::from_hex(c).ok())
+            .unwrap_or_else(|| theme.get_work_color())
+    }
+}
+
+/// The timers as small rings that can be clicked to start and pause, with a way to add more
+pub fn draw_timers(ui: &mut Ui, timers: &mut [ExtraTimer], theme: &Theme) -> Vec<Action> {
+    let mut actions = vec![];
+    ui.horizontal_wrapped(|ui| {
+        for (i, timer) in timers.iter_mut().enumerate() {
+            ui.vertical(|ui| {
+                let percent = timer
+                    .timer
+                    .to_percent()
+                    .unwrap_or(Percent::new(100.0).expect("Should be valid"));
+                let response = ui.add_sized(
+                    RING_SIZE,
+                    ProgressCircle::new(percent, timer, theme).compact(true),
+                );
+                response.context_menu(|ui| {
+                    if ui.button("Reset").clicked() {
+                        actions.push(Action::ResetTimer(i));
+                        ui.close_menu();
+                    }
+                    if ui.button("Remove").clicked() {
+                        actions.push(Action::RemoveTimer(i));
+                        ui.close_menu();
+                    }
+                });
+                // the number key that starts and pauses it
+                let label = match i {
+                    0..=8 => format!("{} {}", i + 1, timer.timer.get_name()),
+                    _ => timer.timer.get_name().to_string(),
+                };
+                ui.label(label);
+            });
+        }
+    });
+
+    ui.collapsing("Add timer", |ui| {
+        let id = ui.id().with("new_timer");
+        let (mut name, mut minutes) = ui.data_mut(|d| {
+            d.get_temp::<(String, u64)>(id)
+                .unwrap_or(("Timer".to_string(), 10))
+        });
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut name);
+            ui.add(
+                DragValue::new(&mut minutes)
+                    .clamp_range(1..=600)
+                    .suffix(" min"),
+            );
+            if ui.button("Add").clicked() && !name.is_empty() {
+                actions.push(Action::AddTimer(TimerConfig {
+                    name: name.clone(),
+                    length: Duration::from_secs(minutes * 60),
+                    color: None,
+                    sound: None,
+                }));
+            }
+        });
+        ui.data_mut(|d| d.insert_temp(id, (name, minutes)));
+    });
+    actions
+}
@@ -0,0 +1,189 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use eframe::{egui, epaint::Color32};
+use ksni::blocking::{Handle, TrayMethods};
+
+use pomodoro_core::{config::Theme, format_time, Timer};
+
+use crate::{circle_widget::RingTimer, theme, ui::Action, utils::load_icon};
+
+/// What the tray shows, only sent to the tray when it changes
+#[derive(Clone, PartialEq, Default)]
+struct TrayState {
+    name: String,
+    time: String,
+    counts_up: bool,
+    color: Color32,
+    paused: bool,
+    profile: Option<String>,
+}
+
+struct PomodoroTray {
+    ctx: egui::Context,
+    actions: Sender<Action>,
+    icon: egui::IconData,
+    profiles: Vec<String>,
+    state: TrayState,
+}
+
+impl PomodoroTray {
+    fn send(&self, action: Action) {
+        let _ = self.actions.send(action);
+        // wakes the app up so the action gets handled even if the window is hidden
+        self.ctx.request_repaint();
+    }
+}
+
+impl ksni::Tray for PomodoroTray {
+    fn id(&self) -> String {
+        "pomodoro".into()
+    }
+
+    fn title(&self) -> String {
+        "Pomodoro Timer".into()
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        self.send(Action::ShowWindow);
+    }
+
+    fn icon_pixmap(&self) -> Vec<ksni::Icon> {
+        let mut data = self.icon.rgba.clone();
+        let [r, g, b, _] = self.state.color.to_array();
+        for pixel in data.chunks_exact_mut(4) {
+            pixel[0] = (pixel[0] as u16 * r as u16 / 255) as u8;
+            pixel[1] = (pixel[1] as u16 * g as u16 / 255) as u8;
+            pixel[2] = (pixel[2] as u16 * b as u16 / 255) as u8;
+            // rgba to argb
+            pixel.rotate_right(1);
+        }
+        vec![ksni::Icon {
+            width: self.icon.width as i32,
+            height: self.icon.height as i32,
+            data,
+        }]
+    }
+
+    fn tool_tip(&self) -> ksni::ToolTip {
+        ksni::ToolTip {
+            title: format!(
+                "{}: {} {}",
+                self.state.name,
+                self.state.time,
+                if self.state.counts_up {
+                    "so far"
+                } else {
+                    "left"
+                }
+            ),
+            description: if self.state.paused { "Paused" } else { "" }.into(),
+            ..Default::default()
+        }
+    }
+
+    fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
+        use ksni::menu::*;
+        vec![
+            StandardItem {
+                label: if self.state.paused { "Start" } else { "Pause" }.into(),
+                activate: Box::new(|this: &mut Self| this.send(Action::TogglePhase)),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: "Skip".into(),
+                activate: Box::new(|this: &mut Self| this.send(Action::NextPhase)),
+                ..Default::default()
+            }
+            .into(),
+            SubMenu {
+                label: "Switch profile".into(),
+                enabled: !self.profiles.is_empty(),
+                submenu: vec![RadioGroup {
+                    // nothing is selected when the times don't come from a profile
+                    selected: self
+                        .profiles
+                        .iter()
+                        .position(|p| Some(p) == self.state.profile.as_ref())
+                        .unwrap_or(usize::MAX),
+                    select: Box::new(|this: &mut Self, i| {
+                        if let Some(profile) = this.profiles.get(i) {
+                            this.send(Action::SwitchProfile(profile.clone()));
+                        }
+                    }),
+                    options: self
+                        .profiles
+                        .iter()
+                        .map(|p| RadioItem {
+                            label: p.clone(),
+                            ..Default::default()
+                        })
+                        .collect(),
+                }
+                .into()],
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            StandardItem {
+                label: "Show window".into(),
+                activate: Box::new(|this: &mut Self| this.send(Action::ShowWindow)),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: "Quit".into(),
+                icon_name: "application-exit".into(),
+                activate: Box::new(|this: &mut Self| this.send(Action::Quit)),
+                ..Default::default()
+            }
+            .into(),
+        ]
+    }
+}
+
+pub struct Tray {
+    handle: Handle<PomodoroTray>,
+    actions: Receiver<Action>,
+    state: TrayState,
+}
+
+impl Tray {
+    /// Returns `None` if there is no session bus to put the tray icon on
+    pub fn new(ctx: &egui::Context, profiles: Vec<String>) -> Option<Self> {
+        let (sender, actions) = mpsc::channel();
+        let tray = PomodoroTray {
+            ctx: ctx.clone(),
+            actions: sender,
+            icon: load_icon(),
+            profiles,
+            state: TrayState::default(),
+        };
+        let handle = tray.spawn().ok()?;
+        Some(Self {
+            handle,
+            actions,
+            state: TrayState::default(),
+        })
+    }
+
+    pub fn update(&mut self, timer: &Timer, profile: Option<String>, theme: &Theme) {
+        let state = TrayState {
+            name: RingTimer::get_name(timer).to_string(),
+            time: format_time(RingTimer::get_time(timer)),
+            counts_up: timer.counts_up(),
+            color: theme::phase_color(&egui::Visuals::dark(), timer, theme),
+            paused: timer.is_paused(),
+            profile,
+        };
+        // this gets called every frame, so only talk to dbus when something changed
+        if state != self.state {
+            self.state = state.clone();
+            self.handle.update(|tray| tray.state = state);
+        }
+    }
+
+    pub fn get_actions(&self) -> Vec<Action> {
+        self.actions.try_iter().collect()
+    }
+}
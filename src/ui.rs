@@ -1,15 +1,79 @@
 use std::time::Duration;
 
-use eframe::egui::{Layout, ScrollArea, Slider, Ui};
+use eframe::egui::{
+    accesskit::{Live, Role},
+    ComboBox, Layout, ScrollArea, Slider, Ui, Vec2,
+};
 
-use crate::{config::Status, stats::Stats};
+use pomodoro_core::{
+    config::{Config, Mode, Step, ThemeMode, TimerConfig},
+    format_time,
+    schedule::{LocalTime, Schedule},
+    Timer,
+};
+
+use crate::config::Status;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum WindowLayout {
+    /// The ring with the controls under it
+    Portrait,
+    /// The ring with the controls next to it
+    Landscape,
+    /// Only the ring
+    Tiny,
+}
+
+impl WindowLayout {
+    pub fn from_size(size: Vec2) -> Self {
+        if size.min_elem() < 200.0 {
+            Self::Tiny
+        } else if size.x > size.y * 1.2 {
+            Self::Landscape
+        } else {
+            Self::Portrait
+        }
+    }
+}
 
 pub enum Action {
     NextPhase,
+    /// Finishes open ended flowtime work, unlike `NextPhase` which skips it
+    EndWork,
+    StartPhase,
     TogglePhase,
+    JumpToStep(usize),
+    ToggleCompact,
+    Lap,
+    Reset,
+    PostponeBreak,
+    AcknowledgeReminder,
+    ToggleTimer(usize),
+    ResetTimer(usize),
+    RemoveTimer(usize),
+    AddTimer(TimerConfig),
+    SwitchProfile(String),
+    ShowWindow,
+    Quit,
     None,
     ModifyWorkPhaseConfig(Duration),
     ModifyBreakPhaseConfig(Duration),
+    ModifyMode(Mode),
+    ModifyFlowtimeRatio(f32),
+    ModifyCountdownTime(Duration),
+    ModifyLapLength(Duration),
+    ModifyThemeMode(ThemeMode),
+    ModifyReduceMotion(bool),
+}
+
+/// Screen readers read this out whenever the text changes
+pub fn draw_announcement(ui: &mut Ui, text: &str) {
+    ui.ctx()
+        .accesskit_node_builder(ui.id().with("announcement"), |builder| {
+            builder.set_role(Role::Status);
+            builder.set_live(Live::Polite);
+            builder.set_name(text);
+        });
 }
 
 pub fn draw_header(ui: &mut Ui) {
@@ -20,16 +84,56 @@ pub fn draw_header(ui: &mut Ui) {
     ui.separator();
 }
 
-pub fn draw_stats_bar(ui: &mut Ui, stats: &Stats, status: Status) -> Action {
+/// The eye reminder, for when the notification isn't noticed
+pub fn draw_reminder(ui: &mut Ui, text: &str) -> Action {
+    let mut action = Action::None;
+    ui.horizontal(|ui| {
+        ui.label(text);
+        if ui.button("Done").clicked() {
+            action = Action::AcknowledgeReminder;
+        }
+    });
+    action
+}
+
+pub fn draw_stats_bar(
+    ui: &mut Ui,
+    timer: &Timer,
+    mode: Mode,
+    // which step of how many, when going through a sequence
+    step: Option<(usize, usize)>,
+    // everyone in the team sync session, if there is one
+    peers: Option<&[String]>,
+    laps: usize,
+    status: Status,
+) -> Action {
     let mut action = Action::None;
+    let stats = timer.get_stats();
     ui.horizontal(|ui| {
-        let stats_text = format!(
-            "{}/{}:{}",
-            stats.get_phase_count().0,
-            stats.get_phase_count().1,
-            stats.get_count()
-        );
+        let stats_text = match (mode, step) {
+            (Mode::Pomodoro, Some((i, len))) => {
+                format!("Step {}/{}:{}", i + 1, len, stats.get_count())
+            }
+            (Mode::Pomodoro | Mode::Flowtime, _) => format!(
+                "{}/{}:{}",
+                stats.get_phase_count().0,
+                stats.get_phase_count().1,
+                stats.get_count()
+            ),
+            (Mode::Countdown, _) => "Countdown".to_string(),
+            (Mode::Stopwatch, _) => format!("Lap {}", laps + 1),
+        };
         ui.label(stats_text);
+        match peers {
+            Some([]) => {
+                ui.label("Not connected");
+            }
+            Some(peers) => {
+                ui.label(format!("{} in session", peers.len()))
+                    .on_hover_text(peers.join("\n"));
+            }
+            None => {}
+        }
             match status {
                 Status::Saving => {
                     ui.label("Saving");
@@ -45,43 +149,200 @@ pub fn draw_stats_bar(ui: &mut Ui, stats: &Stats, status: Status) -> Action {
                 Status::Loaded => {
                     ui.label("Loaded");
                 }
+                Status::Error(e) => {
+                    ui.colored_label(ui.visuals().error_fg_color, "Not saved")
+                        .on_hover_text(e);
+                }
                 _ => {}
             };
-        ui.with_layout(Layout::top_down(eframe::emath::Align::Max), |ui| {
-            if ui.button("Skip").clicked() {
-                action = Action::NextPhase;
+        ui.with_layout(Layout::right_to_left(eframe::emath::Align::Center), |ui| {
+            match mode {
+                Mode::Pomodoro | Mode::Flowtime => {
+                    if timer.get_phase().is_open_ended() && ui.button("End work").clicked() {
+                        action = Action::EndWork;
+                    }
+                    if ui.button("Skip").clicked() {
+                        action = Action::NextPhase;
+                    }
+                }
+                Mode::Countdown | Mode::Stopwatch => {
+                    if ui.button("Reset").clicked() {
+                        action = Action::Reset;
+                    }
+                }
+            }
+            if mode == Mode::Stopwatch && ui.button("Lap").clicked() {
+                action = Action::Lap;
+            }
+            if ui.button("Compact").clicked() {
+                action = Action::ToggleCompact;
             }
         })
     });
     action
 }
 
-pub fn draw_config(ui: &mut Ui, work_phase: &Duration, break_phase: &Duration) -> Vec<Action> {
+/// The steps of the sequence, which can be clicked to jump to them
+pub fn draw_sequence(ui: &mut Ui, steps: &[Step], current: usize) -> Action {
+    let mut action = Action::None;
+    ui.horizontal_wrapped(|ui| {
+        for (i, step) in steps.iter().enumerate() {
+            let text = format!("{} {}", step.get_name(), format_time(step.length));
+            if ui.selectable_label(i == current, text).clicked() && i != current {
+                action = Action::JumpToStep(i);
+            }
+        }
+    });
+    action
+}
+
+/// Whether it's working hours and when the next ones start, flagging a phase that runs past them
+pub fn draw_schedule(
+    ui: &mut Ui,
+    schedule: &Schedule,
+    now: LocalTime,
+    phase_left: Option<Duration>,
+) {
+    ui.horizontal_wrapped(|ui| {
+        match schedule.get_end(now) {
+            Some(end) => {
+                ui.label(format!("Working hours until {end}"));
+                let left = schedule.time_left(now).unwrap_or_default();
+                if phase_left.is_some_and(|phase_left| phase_left > left) {
+                    ui.colored_label(ui.visuals().warn_fg_color, "This phase runs past them");
+                }
+            }
+            None => {
+                ui.label("Outside working hours");
+            }
+        }
+        if let Some(next) = schedule.next_session(now) {
+            ui.label(format!("Next session: {next}"));
+        }
+    });
+}
+
+/// How long each lap took, newest first
+pub fn draw_laps(ui: &mut Ui, laps: &[Duration]) {
+    if laps.is_empty() {
+        return;
+    }
+    ScrollArea::vertical()
+        .id_source("laps")
+        .max_height(60.0)
+        .show(ui, |ui| {
+            ui.set_min_width(ui.available_width());
+            for (i, lap) in laps.iter().enumerate().rev() {
+                ui.label(format!("Lap {}: {}", i + 1, format_time(*lap)));
+            }
+        });
+}
+
+/// Only changes `time` once the slider is moved, so lengths that aren't whole minutes, like ones
+/// from the command line or the api, aren't rounded just by being shown
+fn draw_minutes_slider(ui: &mut Ui, label: &str, time: &mut Duration) {
+    let mut minutes = time.as_secs() / 60;
+    let changed = ui
+        .horizontal(|ui| {
+            ui.label(label);
+            ui.add(Slider::new(&mut minutes, 1..=100).text("(min)"))
+                .changed()
+        })
+        .inner;
+    if changed {
+        *time = Duration::from_secs(minutes * 60);
+    }
+}
+
+pub fn draw_config(ui: &mut Ui, config: &Config) -> Vec<Action> {
     ui.separator();
-    let mut work_phase_new = *work_phase;
-    let mut break_phase_new = *break_phase;
+    let theme = &config.theme;
+    let mut mode_new = config.mode;
+    let mut work_phase_new = config.work_time;
+    let mut break_phase_new = config.break_time;
+    let mut ratio_new = config.flowtime.ratio;
+    let mut countdown_new = config.countdown_time;
+    let mut lap_length_new = config.lap_length;
+    let mut theme_mode_new = theme.mode;
+    let mut reduce_motion_new = theme.reduce_motion;
     ScrollArea::vertical().show(ui, |ui| {
         ui.set_min_width(ui.available_width());
-        let mut wtime = work_phase_new.as_secs() / 60;
         ui.horizontal(|ui| {
-            ui.label("Work Time:");
-            ui.add(Slider::new(&mut wtime, 0..=100).text("(min)"));
+            ui.label("Mode:");
+            ui.selectable_value(&mut mode_new, Mode::Pomodoro, "Pomodoro");
+            ui.selectable_value(&mut mode_new, Mode::Flowtime, "Flowtime");
+            ui.selectable_value(&mut mode_new, Mode::Countdown, "Countdown");
+            ui.selectable_value(&mut mode_new, Mode::Stopwatch, "Stopwatch");
         });
-        work_phase_new = Duration::from_secs(wtime * 60);
 
-        let mut btime = break_phase_new.as_secs() / 60;
+        match mode_new {
+            Mode::Pomodoro => {
+                draw_minutes_slider(ui, "Work Time:", &mut work_phase_new);
+                draw_minutes_slider(ui, "Break Time:", &mut break_phase_new);
+            }
+            Mode::Flowtime if config.flowtime.tiers.is_empty() => {
+                ui.horizontal(|ui| {
+                    ui.label("Break Ratio:");
+                    ui.add(Slider::new(&mut ratio_new, 1.0..=10.0).text("(min worked per min)"));
+                });
+            }
+            Mode::Flowtime => {
+                for tier in &config.flowtime.tiers {
+                    ui.label(format!(
+                        "Up to {} min of work: {} min break",
+                        tier.work.as_secs() / 60,
+                        tier.break_time.as_secs() / 60
+                    ));
+                }
+            }
+            Mode::Countdown => draw_minutes_slider(ui, "Countdown:", &mut countdown_new),
+            Mode::Stopwatch => draw_minutes_slider(ui, "Lap Length:", &mut lap_length_new),
+        }
+
+        let modes = [
+            (ThemeMode::Dark, "Dark"),
+            (ThemeMode::Light, "Light"),
+            (ThemeMode::System, "System"),
+            (ThemeMode::HighContrast, "High contrast"),
+        ];
         ui.horizontal(|ui| {
-            ui.label("Break Time:");
-            ui.add(Slider::new(&mut btime, 0..=100).text("(min)"));
+            ui.label("Theme:");
+            let selected = modes.iter().find(|(m, _)| *m == theme_mode_new);
+            ComboBox::from_id_source("theme_mode")
+                .selected_text(selected.map_or("", |(_, name)| *name))
+                .show_ui(ui, |ui| {
+                    for (mode, name) in modes {
+                        ui.selectable_value(&mut theme_mode_new, mode, name);
+                    }
+                });
         });
-        break_phase_new = Duration::from_secs(btime * 60);
+
+        ui.checkbox(&mut reduce_motion_new, "Reduce motion");
     });
     let mut actions = vec![];
-    if work_phase_new != *work_phase {
+    if mode_new != config.mode {
+        actions.push(Action::ModifyMode(mode_new));
+    }
+    if work_phase_new != config.work_time {
         actions.push(Action::ModifyWorkPhaseConfig(work_phase_new));
     }
-    if break_phase_new != *break_phase {
+    if break_phase_new != config.break_time {
         actions.push(Action::ModifyBreakPhaseConfig(break_phase_new));
     }
+    if ratio_new != config.flowtime.ratio {
+        actions.push(Action::ModifyFlowtimeRatio(ratio_new));
+    }
+    if countdown_new != config.countdown_time {
+        actions.push(Action::ModifyCountdownTime(countdown_new));
+    }
+    if lap_length_new != config.lap_length {
+        actions.push(Action::ModifyLapLength(lap_length_new));
+    }
+    if theme_mode_new != theme.mode {
+        actions.push(Action::ModifyThemeMode(theme_mode_new));
+    }
+    if reduce_motion_new != theme.reduce_motion {
+        actions.push(Action::ModifyReduceMotion(reduce_motion_new));
+    }
     actions
 }
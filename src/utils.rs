@@ -1,4 +1,8 @@
+use std::{io::BufReader, path::PathBuf};
+
+use chrono::{Datelike, Local, Timelike};
 use eframe::egui::IconData;
+use pomodoro_core::schedule::{LocalTime, TimeOfDay, Weekday};
 use rodio::{Decoder, OutputStream, Sink};
 #[cfg(windows)]
 use windows_sys::Win32::UI::WindowsAndMessaging::GetSystemMetrics;
@@ -21,6 +25,25 @@ pub fn get_window_size() -> Option<MonitorSize> {
     None
 }
 
+/// When today started in the local timezone, in seconds since the unix epoch
+pub fn start_of_today() -> u64 {
+    Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map_or(0, |midnight| midnight.timestamp() as u64)
+}
+
+/// The time right now in the local timezone, for the schedule
+pub fn local_time() -> LocalTime {
+    let now = Local::now();
+    LocalTime {
+        day: Weekday::from_index(now.weekday().num_days_from_monday() as usize),
+        time: TimeOfDay::new(now.hour() as u16, now.minute() as u16).expect("Should be valid"),
+        second: now.second() as u8,
+    }
+}
+
 pub fn play_sound(sound: &'static [u8]) {
     std::thread::spawn(move || {
         let (_stream, stream_handle) = OutputStream::try_default().unwrap();
@@ -32,6 +55,28 @@ pub fn play_sound(sound: &'static [u8]) {
     });
 }
 
+/// Plays a sound from a file, like the ones set in the config
+pub fn play_sound_file(path: PathBuf) {
+    std::thread::spawn(move || {
+        let file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                println!("Could not open {}: {e}", path.display());
+                return;
+            }
+        };
+        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
+        let sink = Sink::try_new(&stream_handle).unwrap();
+        match Decoder::new(BufReader::new(file)) {
+            Ok(source) => {
+                sink.append(source);
+                sink.sleep_until_end();
+            }
+            Err(e) => println!("Could not play {}: {e}", path.display()),
+        }
+    });
+}
+
 pub fn load_icon() -> IconData {
     let (icon_rgba, icon_width, icon_height) = {
         let image = image::load_from_memory(include_bytes!("./assets/icon.png"))
@@ -48,25 +93,3 @@ pub fn load_icon() -> IconData {
         height: icon_height,
     }
 }
-
-pub struct Percent {
-    percent: f32,
-}
-
-impl Percent {
-    pub fn new(n: f32) -> Result<Self, String> {
-        // this percentage cannot go to 0 because then the indexing will break in the get_points
-        // method
-        if !(0.0..=100.0).contains(&n) {
-            return Err("Out of bounds".to_owned());
-        }
-        Ok(Self { percent: n })
-    }
-
-    pub fn map_to_value<T: Into<f64> + From<f64>>(&self, value: T) -> T {
-        let value: f64 = value.into();
-        let x = (self.percent as f64 / 100.0) * (value);
-        // clamps it because it could go over the value
-        x.clamp(0.0, value).into()
-    }
-}
@@ -0,0 +1,64 @@
+use eframe::{
+    egui::{self, ViewportBuilder},
+    epaint::{vec2, Pos2, Vec2},
+};
+
+const FULL_SIZE: Vec2 = vec2(350.0, 450.0);
+const MIN_SIZE: Vec2 = vec2(100.0, 100.0);
+const COMPACT_SIZE: Vec2 = vec2(150.0, 150.0);
+
+/// Uses the last size and position of the window if they are known
+pub fn full_viewport(size: Option<Vec2>, position: Option<Pos2>) -> ViewportBuilder {
+    let builder = ViewportBuilder::default()
+        .with_decorations(true)
+        .with_resizable(true)
+        .with_inner_size(size.unwrap_or(FULL_SIZE))
+        .with_min_inner_size(MIN_SIZE)
+        .with_window_level(egui::WindowLevel::Normal);
+    match position {
+        Some(pos) => builder.with_position(pos),
+        None => builder,
+    }
+}
+
+/// The same as `full_viewport`, but also lifting the size limit `compact_viewport` sets
+pub fn uncompact_viewport(size: Option<Vec2>, position: Option<Pos2>) -> ViewportBuilder {
+    full_viewport(size, position).with_max_inner_size(Vec2::INFINITY)
+}
+
+// the same kind of window as the notifications, just with only the ring in it
+pub fn compact_viewport(position: Option<Pos2>) -> ViewportBuilder {
+    let builder = ViewportBuilder::default()
+        .with_decorations(false)
+        .with_resizable(false)
+        .with_inner_size(COMPACT_SIZE)
+        .with_max_inner_size(COMPACT_SIZE)
+        .with_always_on_top();
+    match position {
+        Some(pos) => builder.with_position(pos),
+        None => builder,
+    }
+}
+
+/// Turns the current window into `target`, only sending the commands for what changed
+pub fn switch_viewport(
+    ctx: &egui::Context,
+    current: &mut ViewportBuilder,
+    target: ViewportBuilder,
+) {
+    // the root window can't be recreated, so the bool is ignored
+    let (commands, _) = current.patch(target);
+    for command in commands {
+        ctx.send_viewport_cmd(command);
+    }
+}
+
+/// Covers the whole monitor, on top of everything else
+pub fn break_viewport() -> ViewportBuilder {
+    ViewportBuilder::default()
+        .with_title("Break")
+        .with_decorations(false)
+        .with_fullscreen(true)
+        .with_always_on_top()
+        .with_taskbar(false)
+}